@@ -1,40 +1,104 @@
 #![feature(custom_test_frameworks)]
 #![test_runner(criterion::runner)]
 
+use criterion::measurement::{Measurement, ValueFormatter};
 use criterion::{Criterion, SamplingMode, Throughput};
 use criterion_macro::criterion;
+use futures::executor::block_on_stream;
 use lib::nnue::Evaluator;
 use lib::search::{Depth, Engine, Limits, Options};
-use lib::util::{Integer, Trigger};
+use lib::util::Integer;
 use std::thread::available_parallelism;
 use std::time::{Duration, Instant};
 
+/// Measures the number of nodes searched instead of the time elapsed.
+struct Nodes;
+
+impl Measurement for Nodes {
+    type Intermediate = ();
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {}
+
+    fn end(&self, _: Self::Intermediate) -> Self::Value {
+        0
+    }
+
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value {
+        a + b
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+impl ValueFormatter for Nodes {
+    fn scale_values(&self, _: f64, _: &mut [f64]) -> &'static str {
+        "nodes"
+    }
+
+    fn scale_throughputs(&self, _: f64, _: &Throughput, _: &mut [f64]) -> &'static str {
+        "nodes"
+    }
+
+    fn scale_for_machines(&self, _: &mut [f64]) -> &'static str {
+        "nodes"
+    }
+}
+
+fn thread_options() -> Vec<Options> {
+    let thread_limit = match available_parallelism() {
+        Ok(cores) => cores.get().div_ceil(2),
+        Err(_) => 1,
+    };
+
+    Vec::from_iter((0..=thread_limit.ilog2()).map(|threads| Options {
+        threads: 2usize.pow(threads).saturate(),
+        ..Options::default()
+    }))
+}
+
 fn bench(reps: u64, options: &Options, limits: &Limits) -> Duration {
     let mut time = Duration::ZERO;
 
     for _ in 0..reps {
         let e = Engine::with_options(options);
-        let stopper = Trigger::armed();
         let pos = Evaluator::default();
         let timer = Instant::now();
-        e.search(&pos, limits, &stopper);
+        e.search(&pos, limits);
         time += timer.elapsed();
     }
 
     time
 }
 
+fn nodes(reps: u64, options: &Options, limits: &Limits) -> u64 {
+    let mut nodes = 0;
+
+    for _ in 0..reps {
+        let e = Engine::with_options(options);
+        let pos = Evaluator::default();
+        let mut search = e.start(&pos, limits);
+        let progress = block_on_stream(search.subscribe()).last();
+        nodes += progress.map_or(0, |p| p.nodes());
+        search.wait();
+    }
+
+    nodes
+}
+
 #[criterion]
 fn crit(c: &mut Criterion) {
-    let thread_limit = match available_parallelism() {
-        Ok(cores) => cores.get().div_ceil(2),
-        Err(_) => 1,
-    };
-
-    let options = Vec::from_iter((0..=thread_limit.ilog2()).map(|threads| Options {
-        threads: 2usize.pow(threads).saturate(),
-        ..Options::default()
-    }));
+    let options = thread_options();
 
     for o in &options {
         let depth = Depth::new(18);
@@ -55,3 +119,15 @@ fn crit(c: &mut Criterion) {
             });
     }
 }
+
+#[criterion(Criterion::default().with_measurement(Nodes))]
+fn ntd(c: &mut Criterion<Nodes>) {
+    for o in &thread_options() {
+        let depth = Depth::new(18);
+        c.benchmark_group("ntd")
+            .sampling_mode(SamplingMode::Flat)
+            .bench_function(o.threads.to_string(), |b| {
+                b.iter_custom(|i| nodes(i, o, &depth.into()))
+            });
+    }
+}
@@ -1,5 +1,5 @@
 use futures::{channel::mpsc::unbounded, executor::block_on, sink::unfold as sink};
-use lib::uci::Uci;
+use lib::uci::{Uci, UciResponse};
 use std::io::{prelude::*, stdin, stdout};
 use std::{future::ready, thread};
 
@@ -16,6 +16,6 @@ fn main() {
     });
 
     let mut stdout = stdout().lock();
-    let output = sink((), |_, line: String| ready(writeln!(stdout, "{line}")));
+    let output = sink((), |_, r: UciResponse| ready(writeln!(stdout, "{r}")));
     block_on(Uci::new(input, output).run()).unwrap();
 }
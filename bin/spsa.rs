@@ -0,0 +1,48 @@
+use lib::search::{Engine, Limits, Params};
+use lib::tune::{Arena, Spsa};
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+use std::{env, time::SystemTime};
+
+fn main() {
+    let mut args = env::args().skip(1).map(|a| a.parse::<u64>().unwrap());
+    let iterations = args.next().unwrap_or(10000);
+    let nodes = args.next().unwrap_or(5000);
+    let seed = args.next().unwrap_or_else(|| {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+        now.unwrap_or_default().as_nanos() as u64
+    });
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let arena = Arena::new(Limits::Nodes(nodes), 400);
+    let mut spsa = Spsa::new(2., iterations as f64 / 10.);
+    let (mut a, mut b) = (Engine::new(), Engine::new());
+
+    while spsa.iterations() < iterations as u32 {
+        let opening = arena.opening(&mut rng, 8);
+        spsa.step(&mut rng, |plus, minus| {
+            a.set_params(plus);
+            b.set_params(minus);
+            arena.pair(&mut a, &mut b, &opening) as f64 / 2.
+        });
+
+        let params = spsa.params();
+        let values = Params::LIST
+            .iter()
+            .map(|p| format!("{}={}", p.name, params.get(p.name).unwrap()));
+        println!(
+            "{} {}",
+            spsa.iterations(),
+            values.collect::<Vec<_>>().join(" ")
+        );
+    }
+
+    let params = spsa.params();
+    for p in Params::LIST {
+        println!(
+            "setoption name {} value {}",
+            p.name,
+            params.get(p.name).unwrap()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use lib::search::{Engine, Limits, WdlModel};
+use lib::tune::Arena;
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+use std::{env, time::SystemTime};
+
+fn main() {
+    let mut args = env::args().skip(1).map(|a| a.parse::<u64>().unwrap());
+    let games = args.next().unwrap_or(1000);
+    let nodes = args.next().unwrap_or(5000);
+    let seed = args.next().unwrap_or_else(|| {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+        now.unwrap_or_default().as_nanos() as u64
+    });
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let arena = Arena::new(Limits::Nodes(nodes), 400);
+    let mut engine = Engine::new();
+
+    let mut samples = Vec::new();
+    for game in 1..=games {
+        let opening = arena.opening(&mut rng, 8);
+        samples.extend(arena.record(&mut engine, &opening));
+        println!("{game} {}", samples.len());
+    }
+
+    let model = WdlModel::fit(&samples).expect("not enough samples");
+    for (name, coefficients) in [("a", model.a()), ("b", model.b())] {
+        let coefficients = coefficients.map(|c| format!("{c:.2}"));
+        println!("{name} = [{}]", coefficients.join(", "));
+    }
+}
@@ -130,6 +130,37 @@ impl Board {
         zobrist
     }
 
+    /// Computes the [zobrist hash] of the pawn structure.
+    ///
+    /// [zobrist hash]: https://www.chessprogramming.org/Zobrist_Hashing
+    #[inline(always)]
+    pub fn pawn_zobrist(&self) -> Zobrist {
+        let mut zobrist = Zobrist::new(0);
+
+        for (p, sq) in self.iter().filter(|(p, _)| p.role() == Role::Pawn) {
+            zobrist ^= ZobristNumbers::psq(p.color(), p.role(), sq);
+        }
+
+        zobrist
+    }
+
+    /// Computes the [zobrist hash] of the pieces other than pawns of a [`Color`].
+    ///
+    /// [zobrist hash]: https://www.chessprogramming.org/Zobrist_Hashing
+    #[inline(always)]
+    pub fn piece_zobrist(&self, c: Color) -> Zobrist {
+        let mut zobrist = Zobrist::new(0);
+
+        for (p, sq) in self
+            .iter()
+            .filter(|(p, _)| p.role() != Role::Pawn && p.color() == c)
+        {
+            zobrist ^= ZobristNumbers::psq(p.color(), p.role(), sq);
+        }
+
+        zobrist
+    }
+
     /// Toggles a piece on a square.
     #[inline(always)]
     pub fn toggle(&mut self, p: Piece, sq: Square) {
@@ -4,11 +4,53 @@ use arrayvec::{ArrayVec, CapacityError};
 use derive_more::{Debug, Display, Error, From};
 use std::fmt::{self, Formatter};
 use std::hash::{Hash, Hasher};
-use std::{num::NonZeroU32, ops::Index, str::FromStr};
+use std::{mem::replace, num::NonZeroU32, ops::Index, str::FromStr};
 
 #[cfg(test)]
 use proptest::{prelude::*, sample::*};
 
+/// The kinds of moves to generate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    /// All moves.
+    All,
+    /// Captures and promotions only.
+    Noisy,
+    /// Moves that are neither captures nor promotions.
+    Quiet,
+}
+
+impl Mode {
+    /// The squares that may be captured on, given the opponent's material.
+    #[inline(always)]
+    fn captures(self, theirs: Bitboard) -> Bitboard {
+        match self {
+            Mode::Quiet => Bitboard::empty(),
+            _ => theirs,
+        }
+    }
+
+    /// The squares that may be moved to without capturing.
+    #[inline(always)]
+    fn regulars(self, occupied: Bitboard) -> Bitboard {
+        match self {
+            Mode::Noisy => Bitboard::empty(),
+            _ => occupied.inverse(),
+        }
+    }
+
+    /// The squares pawns of a given side may be pushed to.
+    #[inline(always)]
+    fn pushes(self, side: Color) -> Bitboard {
+        let promotions = Rank::Eighth.perspective(side).bitboard();
+        match self {
+            Mode::All => Bitboard::full(),
+            Mode::Noisy => promotions,
+            Mode::Quiet => promotions.inverse(),
+        }
+    }
+}
+
 #[inline(always)]
 fn collect_moves<const N: usize>(
     piece: Piece,
@@ -37,6 +79,7 @@ impl Evasions {
     #[inline(always)]
     fn generate<const N: usize>(
         pos: &Position,
+        mode: Mode,
         buffer: &mut ArrayVec<MoveSet, N>,
     ) -> Result<(), CapacityError<MoveSet>> {
         let turn = pos.turn();
@@ -44,6 +87,7 @@ impl Evasions {
         let theirs = pos.material(!turn);
         let occupied = pos.occupied();
         let king = pos.king(turn);
+        let targets = mode.captures(theirs) | mode.regulars(occupied);
 
         let checks = pos.checkers().iter().fold(Bitboard::empty(), |bb, sq| {
             Bitboard::segment(king, sq).union(bb)
@@ -57,11 +101,15 @@ impl Evasions {
         use Role::*;
         for wc in candidates & pos.board.by_role(Pawn) {
             let piece = Piece::new(Pawn, turn);
-            let moves = piece.moves(wc, ours, theirs) & checks;
+            let moves = piece.moves(wc, ours, theirs) & checks & mode.pushes(turn);
             if !moves.is_empty() {
                 buffer.try_push(MoveSet::regular(piece, wc, moves))?;
             }
 
+            if mode == Mode::Quiet {
+                continue;
+            }
+
             let ep = pos.en_passant().map_or(Bitboard::empty(), Square::bitboard);
             let mut moves = piece.attacks(wc, occupied) & (pos.checkers() | ep);
 
@@ -81,14 +129,14 @@ impl Evasions {
         for role in [Knight, Bishop, Rook, Queen] {
             let piece = Piece::new(role, turn);
             for wc in candidates & pos.board.by_role(role) {
-                let moves = piece.moves(wc, ours, theirs) & (checks | pos.checkers());
+                let moves = piece.moves(wc, ours, theirs) & (checks | pos.checkers()) & targets;
                 collect_moves(piece, wc, moves, theirs, buffer)?;
             }
         }
 
         {
             let piece = Piece::new(King, turn);
-            let mut moves = piece.moves(king, ours, theirs) & !checks;
+            let mut moves = piece.moves(king, ours, theirs) & !checks & targets;
             for wt in moves {
                 if pos.is_threatened(wt, !turn, occupied.without(king)) {
                     moves ^= wt.bitboard();
@@ -108,6 +156,7 @@ impl Moves {
     #[inline(always)]
     fn generate<const N: usize>(
         pos: &Position,
+        mode: Mode,
         buffer: &mut ArrayVec<MoveSet, N>,
     ) -> Result<(), CapacityError<MoveSet>> {
         let turn = pos.turn();
@@ -115,11 +164,12 @@ impl Moves {
         let theirs = pos.material(!turn);
         let occupied = pos.occupied();
         let king = pos.king(turn);
+        let targets = mode.captures(theirs) | mode.regulars(occupied);
 
         use Role::*;
         for wc in ours & pos.board.by_role(Pawn) {
             let piece = Piece::new(Pawn, turn);
-            let mut moves = piece.moves(wc, ours, theirs);
+            let mut moves = piece.moves(wc, ours, theirs) & mode.pushes(turn);
             if pos.pinned().contains(wc) {
                 moves &= Bitboard::line(wc, king);
             }
@@ -128,6 +178,10 @@ impl Moves {
                 buffer.try_push(MoveSet::regular(piece, wc, moves))?;
             }
 
+            if mode == Mode::Quiet {
+                continue;
+            }
+
             let ep = pos.en_passant().map_or(Bitboard::empty(), Square::bitboard);
             let mut moves = piece.attacks(wc, occupied) & (theirs | ep);
             if pos.pinned().contains(wc) {
@@ -150,7 +204,7 @@ impl Moves {
         {
             let piece = Piece::new(Knight, turn);
             for wc in ours & pos.board.by_role(Knight) {
-                let mut moves = piece.moves(wc, ours, theirs);
+                let mut moves = piece.moves(wc, ours, theirs) & targets;
                 if pos.pinned().contains(wc) {
                     moves &= Bitboard::line(wc, king);
                 }
@@ -162,7 +216,7 @@ impl Moves {
         {
             let piece = Piece::new(Bishop, turn);
             for wc in ours & pos.board.by_role(Bishop) {
-                let mut moves = piece.moves(wc, ours, theirs);
+                let mut moves = piece.moves(wc, ours, theirs) & targets;
                 if pos.pinned().contains(wc) {
                     moves &= Bitboard::line(wc, king);
                 }
@@ -174,7 +228,7 @@ impl Moves {
         {
             let piece = Piece::new(Rook, turn);
             for wc in ours & pos.board.by_role(Rook) {
-                let mut moves = piece.moves(wc, ours, theirs);
+                let mut moves = piece.moves(wc, ours, theirs) & targets;
                 if pos.pinned().contains(wc) {
                     moves &= Bitboard::line(wc, king);
                 }
@@ -186,7 +240,7 @@ impl Moves {
         {
             let piece = Piece::new(Queen, turn);
             for wc in ours & pos.board.by_role(Queen) {
-                let mut moves = piece.moves(wc, ours, theirs);
+                let mut moves = piece.moves(wc, ours, theirs) & targets;
                 if pos.pinned().contains(wc) {
                     moves &= Bitboard::line(wc, king);
                 }
@@ -197,13 +251,17 @@ impl Moves {
 
         {
             let piece = Piece::new(King, turn);
-            let mut moves = piece.moves(king, ours, theirs);
+            let mut moves = piece.moves(king, ours, theirs) & targets;
             for wt in moves {
                 if pos.is_threatened(wt, !turn, occupied.without(king)) {
                     moves ^= wt.bitboard();
                 }
             }
 
+            if mode == Mode::Noisy {
+                return collect_moves(piece, king, moves, theirs, buffer);
+            }
+
             if let Some(c) = pos.castles().long(turn) {
                 let b = Square::new(File::B, c.rank());
                 let path = c.bitboard().with(Square::new(File::D, c.rank()));
@@ -232,6 +290,86 @@ impl Moves {
     }
 }
 
+/// The [`Zobrist`] keys of the positions that preceded the current one.
+///
+/// Keys are kept in a ring buffer large enough to hold every reversible move
+/// allowed by the 50-move rule, so popping a key restores the previous history.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[debug("History")]
+struct History {
+    keys: [Zobrist; Self::CAPACITY],
+    len: usize,
+}
+
+impl Default for History {
+    #[inline(always)]
+    fn default() -> Self {
+        History {
+            keys: [Zobrist::new(0); Self::CAPACITY],
+            len: 0,
+        }
+    }
+}
+
+impl History {
+    const CAPACITY: usize = 128;
+
+    /// Appends the key of the position a move is played from.
+    ///
+    /// Returns the key evicted from the ring buffer.
+    #[inline(always)]
+    fn push(&mut self, key: Zobrist) -> Zobrist {
+        let evicted = replace(&mut self.keys[self.len % Self::CAPACITY], key);
+        self.len += 1;
+        evicted
+    }
+
+    /// Removes the most recent key, restoring the one it evicted.
+    #[inline(always)]
+    fn pop(&mut self, evicted: Zobrist) {
+        self.len -= 1;
+        self.keys[self.len % Self::CAPACITY] = evicted;
+    }
+
+    /// Iterates over the last `n` keys, most recent first.
+    #[inline(always)]
+    fn iter(&self, n: usize) -> impl Iterator<Item = Zobrist> + '_ {
+        let n = n.min(self.len).min(Self::CAPACITY);
+        (1..=n).map(|i| self.keys[(self.len - i) % Self::CAPACITY])
+    }
+}
+
+/// The state needed to [unmake](Position::unmake) a [`Move`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Undo {
+    role: Role,
+    capture: Option<(Role, Square)>,
+    castles: Castles,
+    en_passant: Option<Square>,
+    halfmoves: u8,
+    zobrist: Zobrist,
+    pawn_zobrist: Zobrist,
+    piece_zobrist: [Zobrist; 2],
+    checkers: Bitboard,
+    pinned: Bitboard,
+    reversible: u8,
+    evicted: Zobrist,
+}
+
+impl Undo {
+    /// The [`Role`] of the piece moved.
+    #[inline(always)]
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// The [`Role`] of the piece captured and the [`Square`] it was on, if any.
+    #[inline(always)]
+    pub fn capture(&self) -> Option<(Role, Square)> {
+        self.capture
+    }
+}
+
 /// The current position on the board board.
 ///
 /// This type guarantees that it only holds valid positions.
@@ -240,9 +378,12 @@ impl Moves {
 pub struct Position {
     board: Board,
     zobrist: Zobrist,
+    pawn_zobrist: Zobrist,
+    piece_zobrist: [Zobrist; 2],
     checkers: Bitboard,
     pinned: Bitboard,
-    history: [[Option<NonZeroU32>; 32]; 2],
+    history: History,
+    reversible: u8,
 }
 
 impl Default for Position {
@@ -252,9 +393,15 @@ impl Default for Position {
 
         Self {
             zobrist: board.zobrist(),
+            pawn_zobrist: board.pawn_zobrist(),
+            piece_zobrist: [
+                board.piece_zobrist(Color::White),
+                board.piece_zobrist(Color::Black),
+            ],
             checkers: Default::default(),
             pinned: Default::default(),
             history: Default::default(),
+            reversible: 0,
             board,
         }
     }
@@ -378,6 +525,22 @@ impl Position {
         self.zobrist
     }
 
+    /// The [zobrist hash] of this position's pawn structure.
+    ///
+    /// [zobrist hash]: https://www.chessprogramming.org/Zobrist_Hashing
+    #[inline(always)]
+    pub fn pawn_zobrist(&self) -> Zobrist {
+        self.pawn_zobrist
+    }
+
+    /// The [zobrist hash] of the pieces other than pawns of a [`Color`].
+    ///
+    /// [zobrist hash]: https://www.chessprogramming.org/Zobrist_Hashing
+    #[inline(always)]
+    pub fn piece_zobrist(&self, c: Color) -> Zobrist {
+        self.piece_zobrist[c as usize]
+    }
+
     /// [`Square`]s occupied by pieces giving check.
     #[inline(always)]
     pub fn checkers(&self) -> Bitboard {
@@ -390,16 +553,68 @@ impl Position {
         self.pinned
     }
 
+    /// The keys of earlier positions with the same side to move that could repeat.
+    ///
+    /// Positions before the last irreversible move or null move can never repeat.
+    #[inline(always)]
+    fn predecessors(&self) -> impl Iterator<Item = Zobrist> + '_ {
+        let history = self.history.iter(self.reversible.into());
+        history.skip(1).step_by(2)
+    }
+
     /// How many other times this position has repeated.
     #[inline(always)]
     pub fn repetitions(&self) -> usize {
-        match NonZeroU32::new(self.zobrist().cast()) {
-            None => 0,
-            hash => {
-                let history = &self.history[self.turn() as usize];
-                history.iter().filter(|h| **h == hash).count()
+        self.predecessors().filter(|&k| k == self.zobrist).count()
+    }
+
+    /// The number of plies since this position last occurred, if it repeated.
+    #[inline(always)]
+    pub fn repetition(&self) -> Option<usize> {
+        let i = self.predecessors().position(|k| k == self.zobrist)?;
+        Some(2 * i + 2)
+    }
+
+    /// The number of plies since a position occurred that the side to move can repeat in one move.
+    ///
+    /// This is an implementation of [upcoming repetition detection].
+    ///
+    /// [upcoming repetition detection]: https://www.chessprogramming.org/Upcoming_Repetition_Detection
+    #[inline(always)]
+    pub fn upcoming_repetition(&self) -> Option<usize> {
+        let mut history = self.history.iter(self.reversible.into());
+        let mut other = self.zobrist ^ history.next()? ^ ZobristNumbers::turn();
+
+        for i in (3..).step_by(2) {
+            let (a, b) = (history.next()?, history.next()?);
+            other ^= a ^ b ^ ZobristNumbers::turn();
+            if other != Zobrist::new(0) {
+                continue;
+            }
+
+            let Some((wc, wt)) = ZobristNumbers::cuckoo(self.zobrist ^ b) else {
+                continue;
+            };
+
+            if !Bitboard::segment(wc, wt)
+                .intersection(self.occupied())
+                .is_empty()
+            {
+                continue;
+            }
+
+            let (wc, wt) = match self[wc] {
+                Some(_) => (wc, wt),
+                None => (wt, wc),
+            };
+
+            let mut moves = self.moves().filter(|ms| ms.whence() == wc).flatten();
+            if moves.any(|m| m.whither() == wt) {
+                return Some(i);
             }
         }
+
+        None
     }
 
     /// Whether a [`Square`] is threatened by a piece of a [`Color`].
@@ -432,7 +647,7 @@ impl Position {
     /// [checkmate]: https://www.chessprogramming.org/Checkmate
     #[inline(always)]
     pub fn is_checkmate(&self) -> bool {
-        self.is_check() && Evasions::generate(self, &mut ArrayVec::<_, 0>::new()).is_ok()
+        self.is_check() && Evasions::generate(self, Mode::All, &mut ArrayVec::<_, 0>::new()).is_ok()
     }
 
     /// Whether this position is a [stalemate].
@@ -440,7 +655,7 @@ impl Position {
     /// [stalemate]: https://www.chessprogramming.org/Stalemate
     #[inline(always)]
     pub fn is_stalemate(&self) -> bool {
-        !self.is_check() && Moves::generate(self, &mut ArrayVec::<_, 0>::new()).is_ok()
+        !self.is_check() && Moves::generate(self, Mode::All, &mut ArrayVec::<_, 0>::new()).is_ok()
     }
 
     /// Whether the game is a draw by [Threefold repetition].
@@ -495,23 +710,361 @@ impl Position {
         }
     }
 
+    #[inline(always)]
+    fn generate(&self, mode: Mode) -> impl Iterator<Item = MoveSet> {
+        let mut moves = ArrayVec::<_, 32>::new();
+
+        if self.is_check() {
+            Evasions::generate(self, mode, &mut moves).assume()
+        } else {
+            Moves::generate(self, mode, &mut moves).assume()
+        }
+
+        moves.into_iter()
+    }
+
     /// An iterator over the legal moves that can be played in this position.
     #[inline(always)]
     pub fn moves(&self) -> impl Iterator<Item = MoveSet> {
+        self.generate(Mode::All)
+    }
+
+    /// An iterator over the legal captures and promotions.
+    #[inline(always)]
+    pub fn captures(&self) -> impl Iterator<Item = MoveSet> {
+        self.generate(Mode::Noisy)
+    }
+
+    /// An iterator over the legal moves that are neither captures nor promotions.
+    #[inline(always)]
+    pub fn quiets(&self) -> impl Iterator<Item = MoveSet> {
+        self.generate(Mode::Quiet)
+    }
+
+    /// An iterator over the legal moves that get out of check, if in check.
+    #[inline(always)]
+    pub fn evasions(&self) -> impl Iterator<Item = MoveSet> {
         let mut moves = ArrayVec::<_, 32>::new();
 
         if self.is_check() {
-            Evasions::generate(self, &mut moves).assume()
-        } else {
-            Moves::generate(self, &mut moves).assume()
+            Evasions::generate(self, Mode::All, &mut moves).assume()
         }
 
         moves.into_iter()
     }
 
+    /// Whether a [`Move`] is consistent with the pieces on the board,
+    /// regardless of whether it leaves the king in check.
+    ///
+    /// Castling moves are only required to be allowed by the castling rights
+    /// and have their path between the king and the rook unobstructed.
+    #[inline(always)]
+    pub fn is_pseudo_legal(&self, m: Move) -> bool {
+        use {Rank::*, Role::*};
+
+        let turn = self.turn();
+        let (wc, wt) = (m.whence(), m.whither());
+        if wc == wt {
+            return false;
+        }
+
+        let canonical = match m.is_capture() {
+            false => Move::regular(wc, wt, m.promotion()),
+            true => Move::capture(wc, wt, m.promotion()),
+        };
+
+        // Garbage such as hash collisions may not even be encoded like generated moves.
+        if m != canonical {
+            return false;
+        }
+
+        let Some(piece) = self[wc].filter(|p| p.color() == turn) else {
+            return false;
+        };
+
+        let ours = self.material(turn);
+        let theirs = self.material(!turn);
+        let occupied = self.occupied();
+
+        if piece.role() != Pawn {
+            let moves = piece.moves(wc, ours, theirs);
+            if m.is_promotion() {
+                return false;
+            } else if m.is_capture() {
+                return moves.intersection(theirs).contains(wt);
+            } else if moves.contains(wt) {
+                return !theirs.contains(wt);
+            } else if piece.role() != King {
+                return false;
+            }
+
+            return match (self.castles().long(turn), self.castles().short(turn)) {
+                (Some(c), _) if c == wt && wc == Square::E1.perspective(turn) => {
+                    let path = Bitboard::segment(wc, Square::A1.perspective(turn));
+                    (occupied & path).is_empty()
+                }
+
+                (_, Some(g)) if g == wt && wc == Square::E1.perspective(turn) => {
+                    let path = Bitboard::segment(wc, Square::H1.perspective(turn));
+                    (occupied & path).is_empty()
+                }
+
+                _ => false,
+            };
+        }
+
+        if m.is_promotion() != (wt.rank().perspective(turn) == Eighth) {
+            return false;
+        }
+
+        if m.is_capture() {
+            let ep = self
+                .en_passant()
+                .map_or(Bitboard::empty(), Square::bitboard);
+            piece
+                .attacks(wc, occupied)
+                .intersection(theirs | ep)
+                .contains(wt)
+        } else {
+            piece.moves(wc, ours, theirs).contains(wt)
+        }
+    }
+
+    /// Whether a [`Move`] is legal in this position.
+    ///
+    /// This is equivalent to, but much cheaper than, searching for the move in [`Position::moves`].
+    #[inline(always)]
+    pub fn is_legal(&self, m: Move) -> bool {
+        if !self.is_pseudo_legal(m) {
+            return false;
+        }
+
+        let turn = self.turn();
+        let king = self.king(turn);
+        let occupied = self.occupied();
+        let (wc, wt) = (m.whence(), m.whither());
+
+        if wc == king {
+            if (wt - wc).abs() != 2 {
+                return !self.is_threatened(wt, !turn, occupied.without(king));
+            }
+
+            let path = Bitboard::segment(wc, wt).with(wt);
+            let blockers = occupied.without(king);
+            return !self.is_check()
+                && !path
+                    .iter()
+                    .any(|sq| self.is_threatened(sq, !turn, blockers));
+        }
+
+        if self.pinned().contains(wc) && !Bitboard::line(wc, king).contains(wt) {
+            return false;
+        }
+
+        let checks = match self.checkers().len() {
+            0 => Bitboard::full(),
+            1 if self.pinned().contains(wc) => return false,
+            1 => self.checkers().iter().fold(self.checkers(), |bb, sq| {
+                Bitboard::segment(king, sq).union(bb)
+            }),
+            _ => return false,
+        };
+
+        let theirs = self.material(!turn);
+        if m.is_capture() && !theirs.contains(wt) {
+            // Capturing en passant may expose the king along the rank of the captured pawn.
+            let target = Square::new(wt.file(), wc.rank());
+            let blockers = occupied.without(target).without(wc).with(wt);
+            return !self.is_threatened(king, !turn, blockers);
+        }
+
+        checks.contains(wt)
+    }
+
+    /// An iterator over the legal quiet moves that give check.
+    #[inline(always)]
+    pub fn checks(&self) -> impl Iterator<Item = MoveSet> {
+        use Role::*;
+
+        let turn = self.turn();
+        let king = self.king(!turn);
+        let occupied = self.occupied();
+
+        let mut discoverers = Bitboard::empty();
+        for role in [Queen, Rook, Bishop] {
+            let slider = Piece::new(role, turn);
+            for wc in self.board.by_piece(slider) & slider.targets(king) {
+                let blockers = occupied & Bitboard::segment(king, wc);
+                if blockers.len() == 1 {
+                    discoverers |= blockers & self.material(turn);
+                }
+            }
+        }
+
+        let mut checks = ArrayVec::<_, 32>::new();
+        for ms in self.quiets() {
+            let wc = ms.whence();
+            let piece = self[wc].assume();
+            let mut whither = match piece.role() {
+                King => Bitboard::empty(),
+                _ => piece.flip().attacks(king, occupied),
+            };
+
+            if discoverers.contains(wc) {
+                whither |= Bitboard::line(king, wc).inverse();
+            }
+
+            if piece.role() == King {
+                for wt in ms.whither() {
+                    if (wt - wc).abs() == 2 && self.gives_check(Move::regular(wc, wt, None)) {
+                        whither |= wt.bitboard();
+                    }
+                }
+            }
+
+            let whither = ms.whither() & whither;
+            if !whither.is_empty() {
+                checks.push(MoveSet::regular(piece, wc, whither));
+            }
+        }
+
+        checks.into_iter()
+    }
+
+    /// Whether a [`Move`] gives check.
+    #[inline(always)]
+    pub fn gives_check(&self, m: Move) -> bool {
+        use {Role::*, Square::*};
+
+        let turn = self.turn();
+        let king = self.king(!turn);
+        let (wc, wt) = (m.whence(), m.whither());
+        let role = self.board.role_on(wc).assume();
+        let mut occupied = self.occupied().without(wc).with(wt);
+
+        if role == King && (wt - wc).abs() == 2 {
+            let (wc, wt) = if wt > wc {
+                (H1.perspective(turn), F1.perspective(turn))
+            } else {
+                (A1.perspective(turn), D1.perspective(turn))
+            };
+
+            occupied = occupied.without(wc).with(wt);
+            if Piece::new(Rook, turn).attacks(wt, occupied).contains(king) {
+                return true;
+            }
+        } else if role == Pawn && m.is_capture() && !self.material(!turn).contains(wt) {
+            occupied = occupied.without(Square::new(wt.file(), wc.rank()));
+        }
+
+        let piece = Piece::new(m.promotion().unwrap_or(role), turn);
+        piece.attacks(wt, occupied).contains(king) || self.is_threatened(king, turn, occupied)
+    }
+
+    /// The [`Square`]s occupied by pieces of either side that attack a [`Square`].
+    #[inline(always)]
+    pub fn attackers(&self, sq: Square, occupied: Bitboard) -> Bitboard {
+        let mut attackers = Bitboard::empty();
+        for p in Piece::iter() {
+            attackers |= occupied & self.board.by_piece(p) & p.flip().attacks(sq, occupied);
+        }
+
+        attackers
+    }
+
+    /// Whether the [static exchange evaluation] of a [`Move`] is at least `threshold`.
+    ///
+    /// [static exchange evaluation]: https://www.chessprogramming.org/Static_Exchange_Evaluation
+    #[inline(always)]
+    pub fn see(&self, m: Move, threshold: i16) -> bool {
+        use Role::*;
+
+        const VALUES: [i16; 6] = [100, 320, 330, 500, 900, 0];
+
+        let (wc, wt) = (m.whence(), m.whither());
+        let mut occupied = self.occupied().without(wc).without(wt);
+        let mut gain = match self.board.role_on(wt) {
+            _ if !m.is_capture() => 0,
+            Some(r) => VALUES[r as usize],
+            None => {
+                occupied = occupied.without(Square::new(wt.file(), wc.rank()));
+                VALUES[Pawn as usize]
+            }
+        };
+
+        let mut attacker = self.board.role_on(wc).assume();
+        if let Some(r) = m.promotion() {
+            gain += VALUES[r as usize] - VALUES[Pawn as usize];
+            attacker = r;
+        }
+
+        let mut balance = gain - threshold;
+        if balance < 0 {
+            return false;
+        }
+
+        balance = VALUES[attacker as usize] - balance;
+        if balance <= 0 {
+            return true;
+        }
+
+        let mut side = self.turn();
+        let mut result = true;
+        loop {
+            side = !side;
+            let attackers = self.attackers(wt, occupied);
+            let ours = attackers & self.material(side);
+            if ours.is_empty() {
+                break;
+            }
+
+            result = !result;
+            let (role, sq) = Role::iter()
+                .find_map(|r| Some((r, (ours & self.board.by_role(r)).into_iter().next()?)))
+                .assume();
+
+            // The king may only capture if the square is no longer defended.
+            if role == King {
+                return match (attackers & self.material(!side)).is_empty() {
+                    true => result,
+                    false => !result,
+                };
+            }
+
+            balance = VALUES[role as usize] - balance;
+            if balance < result as i16 {
+                break;
+            }
+
+            occupied = occupied.without(sq);
+        }
+
+        result
+    }
+
+    /// Toggles a piece on a square, updating the [`Zobrist`] hashes incrementally.
+    #[inline(always)]
+    fn toggle(&mut self, p: Piece, sq: Square) {
+        let key = ZobristNumbers::psq(p.color(), p.role(), sq);
+        self.board.toggle(p, sq);
+        self.zobrist ^= key;
+
+        match p.role() {
+            Role::Pawn => self.pawn_zobrist ^= key,
+            _ => self.piece_zobrist[p.color() as usize] ^= key,
+        }
+    }
+
     /// Play a [`Move`].
     #[inline(always)]
     pub fn play(&mut self, m: Move) -> (Role, Option<(Role, Square)>) {
+        let undo = self.make(m);
+        (undo.role, undo.capture)
+    }
+
+    /// Play a [`Move`], returning the state needed to [unmake](Self::unmake) it.
+    #[inline(always)]
+    pub fn make(&mut self, m: Move) -> Undo {
         debug_assert!(self.moves().flatten().any(|n| m == n));
 
         use {Role::*, Square::*};
@@ -526,18 +1079,32 @@ impl Position {
             None => Some((Pawn, Square::new(wt.file(), wc.rank()))),
         };
 
+        let mut undo = Undo {
+            role,
+            capture,
+            castles: self.board.castles,
+            en_passant: self.board.en_passant,
+            halfmoves: self.board.halfmoves,
+            zobrist: self.zobrist,
+            pawn_zobrist: self.pawn_zobrist,
+            piece_zobrist: self.piece_zobrist,
+            checkers: self.checkers,
+            pinned: self.pinned,
+            reversible: self.reversible,
+            evicted: self.zobrist,
+        };
+
         if turn == Color::Black {
             self.board.fullmoves += 1;
         }
 
+        undo.evicted = self.history.push(self.zobrist);
         if role == Pawn || capture.is_some() {
             self.board.halfmoves = 0;
-            self.history = Default::default();
+            self.reversible = 0;
         } else {
             self.board.halfmoves += 1;
-            let entries = self.history[turn as usize].len();
-            self.history[turn as usize].copy_within(..entries - 1, 1);
-            self.history[turn as usize][0] = NonZeroU32::new(self.zobrist().cast());
+            self.reversible = self.reversible.saturating_add(1);
         }
 
         self.board.turn = !self.board.turn;
@@ -548,21 +1115,15 @@ impl Position {
         }
 
         if let Some((victim, target)) = capture {
-            self.board.toggle(Piece::new(victim, !turn), target);
-            self.zobrist ^= ZobristNumbers::psq(!turn, victim, target);
+            self.toggle(Piece::new(victim, !turn), target);
         }
 
-        self.board.toggle(Piece::new(role, turn), wc);
-        self.board.toggle(Piece::new(role, turn), wt);
-
-        self.zobrist ^= ZobristNumbers::psq(turn, role, wc);
-        self.zobrist ^= ZobristNumbers::psq(turn, role, wt);
+        self.toggle(Piece::new(role, turn), wc);
+        self.toggle(Piece::new(role, turn), wt);
 
         if let Some(promotion) = promotion {
-            self.board.toggle(Piece::new(Pawn, turn), wt);
-            self.board.toggle(Piece::new(promotion, turn), wt);
-            self.zobrist ^= ZobristNumbers::psq(turn, Pawn, wt);
-            self.zobrist ^= ZobristNumbers::psq(turn, promotion, wt);
+            self.toggle(Piece::new(Pawn, turn), wt);
+            self.toggle(Piece::new(promotion, turn), wt);
         } else if role == Pawn && (wt - wc).abs() == 16 {
             self.board.en_passant = Some(Square::new(wc.file(), Rank::Third.perspective(turn)));
             self.zobrist ^= ZobristNumbers::en_passant(wc.file());
@@ -573,10 +1134,8 @@ impl Position {
                 (A1.perspective(turn), D1.perspective(turn))
             };
 
-            self.board.toggle(Piece::new(Rook, turn), wc);
-            self.board.toggle(Piece::new(Rook, turn), wt);
-            self.zobrist ^= ZobristNumbers::psq(turn, Rook, wc);
-            self.zobrist ^= ZobristNumbers::psq(turn, Rook, wt);
+            self.toggle(Piece::new(Rook, turn), wc);
+            self.toggle(Piece::new(Rook, turn), wt);
         }
 
         let disrupted = Castles::from(wc) | Castles::from(wt);
@@ -608,7 +1167,50 @@ impl Position {
             }
         }
 
-        (role, capture)
+        undo
+    }
+
+    /// Take back a [`Move`] given the state returned by [`Position::make`].
+    #[inline(always)]
+    pub fn unmake(&mut self, m: Move, undo: Undo) {
+        use {Role::*, Square::*};
+
+        let turn = !self.turn();
+        let (wc, wt) = (m.whence(), m.whither());
+        let role = undo.role;
+
+        self.board
+            .toggle(Piece::new(m.promotion().unwrap_or(role), turn), wt);
+        self.board.toggle(Piece::new(role, turn), wc);
+
+        if let Some((victim, target)) = undo.capture {
+            self.board.toggle(Piece::new(victim, !turn), target);
+        } else if role == King && (wt - wc).abs() == 2 {
+            let (wc, wt) = if wt > wc {
+                (H1.perspective(turn), F1.perspective(turn))
+            } else {
+                (A1.perspective(turn), D1.perspective(turn))
+            };
+
+            self.board.toggle(Piece::new(Rook, turn), wt);
+            self.board.toggle(Piece::new(Rook, turn), wc);
+        }
+
+        if turn == Color::Black {
+            self.board.fullmoves -= 1;
+        }
+
+        self.board.turn = turn;
+        self.board.castles = undo.castles;
+        self.board.en_passant = undo.en_passant;
+        self.board.halfmoves = undo.halfmoves;
+        self.zobrist = undo.zobrist;
+        self.pawn_zobrist = undo.pawn_zobrist;
+        self.piece_zobrist = undo.piece_zobrist;
+        self.checkers = undo.checkers;
+        self.pinned = undo.pinned;
+        self.reversible = undo.reversible;
+        self.history.pop(undo.evicted);
     }
 
     /// Play a [null-move].
@@ -624,9 +1226,8 @@ impl Position {
         }
 
         self.board.halfmoves += 1;
-        let entries = self.history[turn as usize].len();
-        self.history[turn as usize].copy_within(..entries - 1, 1);
-        self.history[turn as usize][0] = NonZeroU32::new(self.zobrist.cast());
+        self.history.push(self.zobrist);
+        self.reversible = 0;
 
         self.board.turn = !self.board.turn;
         self.zobrist ^= ZobristNumbers::turn();
@@ -726,7 +1327,13 @@ impl FromStr for Position {
             checkers,
             pinned,
             zobrist: board.zobrist(),
+            pawn_zobrist: board.pawn_zobrist(),
+            piece_zobrist: [
+                board.piece_zobrist(Color::White),
+                board.piece_zobrist(Color::Black),
+            ],
             history: Default::default(),
+            reversible: 0,
             board,
         })
     }
@@ -783,6 +1390,16 @@ mod tests {
         assert_eq!(pos.zobrist(), pos.board.zobrist());
     }
 
+    #[proptest]
+    fn pawn_zobrist_hashes_the_pawn_structure(pos: Position) {
+        assert_eq!(pos.pawn_zobrist(), pos.board.pawn_zobrist());
+    }
+
+    #[proptest]
+    fn piece_zobrist_hashes_the_pieces_other_than_pawns(pos: Position, c: Color) {
+        assert_eq!(pos.piece_zobrist(c), pos.board.piece_zobrist(c));
+    }
+
     #[proptest]
     fn checkmate_implies_outcome(pos: Position) {
         assert!(!pos.is_checkmate() || pos.outcome() == Some(Outcome::Checkmate(!pos.turn())));
@@ -899,6 +1516,244 @@ mod tests {
         }
     }
 
+    #[proptest]
+    fn captures_and_quiets_partition_moves(pos: Position) {
+        let mut moves = Vec::from_iter(pos.moves().flatten());
+        let mut modes = Vec::from_iter(pos.captures().chain(pos.quiets()).flatten());
+        moves.sort_by_key(|m| m.to_string());
+        modes.sort_by_key(|m| m.to_string());
+        assert_eq!(modes, moves);
+    }
+
+    #[proptest]
+    fn captures_generates_captures_and_promotions(pos: Position) {
+        assert!(pos.captures().flatten().all(|m| !m.is_quiet()));
+    }
+
+    #[proptest]
+    fn quiets_generates_neither_captures_nor_promotions(pos: Position) {
+        assert!(pos.quiets().flatten().all(|m| m.is_quiet()));
+    }
+
+    #[proptest]
+    fn evasions_generates_all_moves_if_in_check(#[filter(#pos.is_check())] pos: Position) {
+        assert_eq!(
+            Vec::from_iter(pos.evasions().flatten()),
+            Vec::from_iter(pos.moves().flatten())
+        );
+    }
+
+    #[proptest]
+    fn evasions_generates_nothing_if_not_in_check(#[filter(!#pos.is_check())] pos: Position) {
+        assert_eq!(pos.evasions().count(), 0);
+    }
+
+    #[proptest]
+    fn modes_agree_with_moves_in_perft(pos: Position) {
+        fn perft(pos: &mut Position, depth: u8) -> (usize, usize) {
+            let moves = pos.moves().map(|ms| ms.iter().len()).sum();
+            let modes = pos
+                .captures()
+                .chain(pos.quiets())
+                .map(|ms| ms.iter().len())
+                .sum();
+            if depth == 0 {
+                return (moves, modes);
+            }
+
+            pos.moves().flatten().fold((moves, modes), |(a, b), m| {
+                let undo = pos.make(m);
+                let (c, d) = perft(pos, depth - 1);
+                pos.unmake(m, undo);
+                (a + c, b + d)
+            })
+        }
+
+        let (moves, modes) = perft(&mut pos.clone(), 2);
+        assert_eq!(modes, moves);
+    }
+
+    #[proptest]
+    fn legal_moves_are_legal(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+    ) {
+        assert!(pos.is_pseudo_legal(m));
+        assert!(pos.is_legal(m));
+    }
+
+    #[proptest]
+    fn is_legal_agrees_with_moves(pos: Position, m: Move) {
+        assert_eq!(pos.is_legal(m), pos.moves().flatten().any(|n| n == m));
+    }
+
+    #[proptest]
+    fn is_legal_agrees_with_moves_of_own_pieces(
+        pos: Position,
+        #[map(|s: Selector| s.select(#pos.material(#pos.turn())))] wc: Square,
+        #[filter(#wt != #wc)] wt: Square,
+        c: bool,
+        #[strategy(prop::option::of(select(vec![Role::Knight, Role::Bishop, Role::Rook, Role::Queen])))]
+        p: Option<Role>,
+    ) {
+        let m = match c {
+            true => Move::capture(wc, wt, p),
+            false => Move::regular(wc, wt, p),
+        };
+
+        assert_eq!(pos.is_legal(m), pos.moves().flatten().any(|n| n == m));
+    }
+
+    #[proptest]
+    fn is_legal_agrees_with_moves_from_other_positions(
+        pos: Position,
+        #[filter(#_other.outcome().is_none())] _other: Position,
+        #[map(|s: Selector| s.select(#_other.moves().flatten()))] m: Move,
+    ) {
+        assert_eq!(pos.is_legal(m), pos.moves().flatten().any(|n| n == m));
+    }
+
+    #[proptest]
+    fn is_legal_implies_is_pseudo_legal(pos: Position, m: Move) {
+        assert!(!pos.is_legal(m) || pos.is_pseudo_legal(m));
+    }
+
+    #[proptest]
+    fn is_pseudo_legal_rejects_moves_of_opponent_pieces(
+        pos: Position,
+        #[map(|s: Selector| s.select(#pos.material(!#pos.turn())))] wc: Square,
+        #[filter(#wt != #wc)] wt: Square,
+    ) {
+        assert!(!pos.is_pseudo_legal(Move::regular(wc, wt, None)));
+        assert!(!pos.is_pseudo_legal(Move::capture(wc, wt, None)));
+    }
+
+    #[proptest]
+    fn gives_check_agrees_with_playing_move(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+    ) {
+        let mut next = pos.clone();
+        next.play(m);
+        assert_eq!(pos.gives_check(m), next.is_check());
+    }
+
+    #[proptest]
+    fn checks_generates_quiet_moves_that_give_check(pos: Position) {
+        let moves = pos
+            .moves()
+            .flatten()
+            .filter(|m| m.is_quiet() && pos.gives_check(*m));
+        let mut expected = Vec::from_iter(moves);
+        let mut checks = Vec::from_iter(pos.checks().flatten());
+        expected.sort_by_key(|m| m.to_string());
+        checks.sort_by_key(|m| m.to_string());
+        assert_eq!(checks, expected);
+    }
+
+    #[proptest]
+    fn see_is_monotonic_in_threshold(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+        #[strategy(-2000i16..2000)] t: i16,
+    ) {
+        assert!(!pos.see(m, t) || pos.see(m, t - 1));
+    }
+
+    #[proptest]
+    fn see_of_quiet_move_is_never_positive(
+        #[filter(#pos.quiets().flatten().next().is_some())] pos: Position,
+        #[map(|s: Selector| s.select(#pos.quiets().flatten()))] m: Move,
+    ) {
+        assert!(!pos.see(m, 1));
+    }
+
+    #[test]
+    fn see_evaluates_exchanges() {
+        for (fen, uci, value) in [
+            ("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5", 100),
+            ("4k3/8/2p5/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5", 0),
+            ("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", "d1d5", -800),
+            ("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5", 100),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100),
+        ] {
+            let pos: Position = fen.parse().unwrap();
+            let m = pos
+                .moves()
+                .flatten()
+                .find(|m| m.to_string() == uci)
+                .unwrap();
+
+            assert!(pos.see(m, value), "{fen} {uci}");
+            assert!(!pos.see(m, value + 1), "{fen} {uci}");
+        }
+    }
+
+    #[test]
+    fn gives_check_detects_special_checks() {
+        for (fen, uci) in [
+            ("8/8/8/8/8/8/8/R3K2k w Q - 0 1", "e1c1"),
+            ("8/8/8/RPp4k/8/8/8/K7 w - c6 0 1", "b5c6"),
+            ("7k/2P5/8/8/8/8/8/K7 w - - 0 1", "c7c8q"),
+            ("7k/8/8/8/3N4/8/8/B6K w - - 0 1", "d4f5"),
+        ] {
+            let pos: Position = fen.parse().unwrap();
+            let m = pos
+                .moves()
+                .flatten()
+                .find(|m| m.to_string() == uci)
+                .unwrap();
+            assert!(pos.gives_check(m), "{fen} {uci}");
+        }
+    }
+
+    #[proptest]
+    fn make_is_equivalent_to_play(
+        #[filter(#pos.outcome().is_none())] mut pos: Position,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+    ) {
+        let mut next = pos.clone();
+        let (role, capture) = next.play(m);
+        let undo = pos.make(m);
+
+        assert_eq!((undo.role(), undo.capture()), (role, capture));
+        assert_eq!(pos.board, next.board);
+        assert_eq!(pos.zobrist, next.zobrist);
+        assert_eq!(pos.checkers, next.checkers);
+        assert_eq!(pos.pinned, next.pinned);
+        assert_eq!(pos.history, next.history);
+        assert_eq!(pos.reversible, next.reversible);
+    }
+
+    #[proptest]
+    fn unmake_restores_position(
+        #[filter(#pos.outcome().is_none())] mut pos: Position,
+        #[strategy(1..16usize)] n: usize,
+        s: Selector,
+    ) {
+        let mut stack = Vec::new();
+        for _ in 0..n {
+            if pos.outcome().is_some() {
+                break;
+            }
+
+            let m = s.select(pos.moves().flatten());
+            stack.push((pos.clone(), m, pos.make(m)));
+        }
+
+        for (prev, m, undo) in stack.into_iter().rev() {
+            pos.unmake(m, undo);
+            assert_eq!(pos.board, prev.board);
+            assert_eq!(pos.zobrist, prev.zobrist);
+            assert_eq!(pos.pawn_zobrist, prev.pawn_zobrist);
+            assert_eq!(pos.piece_zobrist, prev.piece_zobrist);
+            assert_eq!(pos.checkers, prev.checkers);
+            assert_eq!(pos.pinned, prev.pinned);
+            assert_eq!(pos.history, prev.history);
+            assert_eq!(pos.reversible, prev.reversible);
+        }
+    }
+
     #[proptest]
     #[should_panic]
     fn play_panics_if_move_illegal(
@@ -932,15 +1787,131 @@ mod tests {
     }
 
     #[proptest]
-    fn threefold_repetition_implies_draw(#[filter(#pos.outcome().is_none() )] mut pos: Position) {
-        let zobrist = NonZeroU32::new(pos.zobrist().cast());
-        prop_assume!(zobrist.is_some());
+    fn threefold_repetition_implies_draw(
+        #[filter(#pos.outcome().is_none())] mut pos: Position,
+        k: Zobrist,
+    ) {
+        for key in [pos.zobrist(), k, pos.zobrist(), k] {
+            pos.history.push(key);
+        }
 
-        pos.history[pos.turn() as usize][..2].clone_from_slice(&[zobrist, zobrist]);
+        pos.reversible = 4;
         assert!(pos.is_draw_by_threefold_repetition());
         assert_eq!(pos.outcome(), Some(Outcome::DrawByThreefoldRepetition));
     }
 
+    #[proptest]
+    fn repetition_is_detected_far_back_in_history(
+        mut pos: Position,
+        #[filter(#k != #pos.zobrist())] k: Zobrist,
+    ) {
+        pos.history.push(pos.zobrist());
+        for _ in 0..97 {
+            pos.history.push(k);
+        }
+
+        pos.reversible = 98;
+        assert_eq!(pos.repetitions(), 1);
+        assert_eq!(pos.repetition(), Some(98));
+    }
+
+    #[proptest]
+    fn repetition_is_not_detected_across_irreversible_moves(
+        mut pos: Position,
+        #[filter(#k != #pos.zobrist())] k: Zobrist,
+    ) {
+        for key in [pos.zobrist(), k] {
+            pos.history.push(key);
+        }
+
+        pos.reversible = 0;
+        assert_eq!(pos.repetitions(), 0);
+        assert_eq!(pos.repetition(), None);
+    }
+
+    #[test]
+    fn shuffling_pieces_repeats_position() {
+        let mut pos = Position::default();
+
+        for (i, m) in ["g1f3", "g8f6", "f3g1", "f6g8"]
+            .iter()
+            .cycle()
+            .take(8)
+            .enumerate()
+        {
+            let m = pos.moves().flatten().find(|n| n.to_string() == *m).assume();
+            pos.play(m);
+
+            match i {
+                0..3 => assert_eq!(pos.repetition(), None),
+                3..7 => assert_eq!((pos.repetitions(), pos.repetition()), (1, Some(4))),
+                _ => assert_eq!(pos.outcome(), Some(Outcome::DrawByThreefoldRepetition)),
+            }
+        }
+    }
+
+    #[test]
+    fn upcoming_repetition_is_detected_after_shuffling_pieces() {
+        let mut pos = Position::default();
+        assert_eq!(pos.upcoming_repetition(), None);
+
+        for (i, m) in ["g1f3", "g8f6", "f3g1"].into_iter().enumerate() {
+            let m = pos.moves().flatten().find(|n| n.to_string() == m).assume();
+            pos.play(m);
+
+            match i {
+                2 => assert_eq!(pos.upcoming_repetition(), Some(3)),
+                _ => assert_eq!(pos.upcoming_repetition(), None),
+            }
+        }
+    }
+
+    #[proptest]
+    fn upcoming_repetition_can_be_forced_in_one_move(mut pos: Position, s: Selector, t: Selector) {
+        let reversible = |pos: &Position| {
+            let moves = pos.moves().flatten().filter(|m| m.is_quiet());
+            Vec::from_iter(
+                moves.filter(|m| pos[m.whence()].is_some_and(|p| p.role() != Role::Pawn)),
+            )
+        };
+
+        let a = reversible(&pos);
+        prop_assume!(!a.is_empty());
+        let a = s.select(a);
+        pos.play(a);
+
+        let b = reversible(&pos);
+        prop_assume!(!b.is_empty());
+        pos.play(t.select(b));
+
+        let c = pos
+            .moves()
+            .flatten()
+            .find(|m| (m.whence(), m.whither()) == (a.whither(), a.whence()));
+        prop_assume!(c.is_some());
+        pos.play(c.assume());
+
+        let i = pos.upcoming_repetition();
+        prop_assume!(i.is_some());
+
+        assert!(pos.moves().flatten().any(|m| {
+            let mut next = pos.clone();
+            next.play(m);
+            next.repetition().map(|d| d - 1) == i
+        }));
+    }
+
+    #[proptest]
+    fn pass_trims_history(
+        #[filter(!#pos.is_check() && #pos.en_passant().is_none())] mut pos: Position,
+    ) {
+        let zobrist = pos.zobrist();
+        pos.pass();
+        pos.pass();
+        assert_eq!(pos.zobrist(), zobrist);
+        assert_eq!(pos.repetition(), None);
+    }
+
     #[proptest]
     fn parsing_printed_position_is_an_identity(pos: Position) {
         assert_eq!(pos.to_string().parse(), Ok(pos));
@@ -2,7 +2,7 @@ use crate::chess::*;
 use crate::util::{Assume, Bits, Integer};
 use rand::prelude::*;
 use rand_pcg::Pcg64;
-use std::{cell::SyncUnsafeCell, mem::MaybeUninit};
+use std::{cell::SyncUnsafeCell, mem::swap, mem::MaybeUninit};
 
 /// A type representing a [`Position`]'s [zobrist hash].
 ///
@@ -15,6 +15,8 @@ pub struct ZobristNumbers {
     castles: [u64; 16],
     en_passant: [u64; 8],
     turn: u64,
+    cuckoo: [u64; 8192],
+    cuckoo_moves: [(Square, Square); 8192],
 }
 
 static ZOBRIST: SyncUnsafeCell<ZobristNumbers> = unsafe { MaybeUninit::zeroed().assume_init() };
@@ -29,6 +31,71 @@ unsafe fn init() {
     zobrist.castles = rng.gen();
     zobrist.en_passant = rng.gen();
     zobrist.turn = rng.gen();
+
+    // Every reversible move is keyed by the difference it makes to the zobrist hash.
+    for color in Color::iter() {
+        for role in [
+            Role::Knight,
+            Role::Bishop,
+            Role::Rook,
+            Role::Queen,
+            Role::King,
+        ] {
+            for a in Square::iter() {
+                for b in Square::iter().filter(|&b| a < b && is_reachable(role, a, b)) {
+                    let psq = &zobrist.pieces[color as usize][role as usize];
+                    let mut key = psq[a as usize] ^ psq[b as usize] ^ zobrist.turn;
+                    let mut m = (a, b);
+                    let mut i = cuckoo::h1(key);
+
+                    loop {
+                        swap(&mut zobrist.cuckoo[i], &mut key);
+                        swap(&mut zobrist.cuckoo_moves[i], &mut m);
+
+                        if key == 0 {
+                            break;
+                        }
+
+                        i = match i == cuckoo::h1(key) {
+                            true => cuckoo::h2(key),
+                            false => cuckoo::h1(key),
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether a piece of some [`Role`] can move between two squares on an empty board.
+#[inline(always)]
+fn is_reachable(role: Role, a: Square, b: Square) -> bool {
+    let df = (b.file() - a.file()).abs();
+    let dr = (b.rank() - a.rank()).abs();
+
+    match role {
+        Role::Pawn => false,
+        Role::Knight => df.min(dr) == 1 && df.max(dr) == 2,
+        Role::Bishop => df == dr && df > 0,
+        Role::Rook => (df == 0) != (dr == 0),
+        Role::Queen => (df == dr && df > 0) || (df == 0) != (dr == 0),
+        Role::King => df.max(dr) == 1,
+    }
+}
+
+/// The hash functions of the [cuckoo] table.
+///
+/// [cuckoo]: https://www.chessprogramming.org/Upcoming_Repetition_Detection
+mod cuckoo {
+    #[inline(always)]
+    pub fn h1(key: u64) -> usize {
+        (key & 0x1FFF) as usize
+    }
+
+    #[inline(always)]
+    pub fn h2(key: u64) -> usize {
+        ((key >> 16) & 0x1FFF) as usize
+    }
 }
 
 impl ZobristNumbers {
@@ -54,4 +121,49 @@ impl ZobristNumbers {
     pub fn turn() -> Zobrist {
         Zobrist::new(unsafe { ZOBRIST.get().as_ref_unchecked().turn })
     }
+
+    /// The reversible move that changes the zobrist hash by `key`, if any.
+    ///
+    /// The move is returned as a pair of squares in no particular order.
+    #[inline(always)]
+    pub fn cuckoo(key: Zobrist) -> Option<(Square, Square)> {
+        let zobrist = unsafe { ZOBRIST.get().as_ref_unchecked() };
+        let key = key.get();
+
+        [cuckoo::h1(key), cuckoo::h2(key)]
+            .into_iter()
+            .find(|&i| zobrist.cuckoo[i] == key)
+            .map(|i| zobrist.cuckoo_moves[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[test]
+    fn cuckoo_table_holds_every_reversible_move() {
+        let zobrist = unsafe { ZOBRIST.get().as_ref_unchecked() };
+        assert_eq!(zobrist.cuckoo.iter().filter(|&&k| k != 0).count(), 3668);
+    }
+
+    #[proptest]
+    fn cuckoo_finds_reversible_moves(
+        c: Color,
+        #[filter(#r != Role::Pawn)] r: Role,
+        a: Square,
+        #[filter(is_reachable(#r, #a, #b))] b: Square,
+    ) {
+        let key =
+            ZobristNumbers::psq(c, r, a) ^ ZobristNumbers::psq(c, r, b) ^ ZobristNumbers::turn();
+        let m = ZobristNumbers::cuckoo(key);
+        assert!(m == Some((a, b)) || m == Some((b, a)));
+    }
+
+    #[proptest]
+    fn cuckoo_ignores_pawn_moves(c: Color, a: Square, b: Square) {
+        let key = ZobristNumbers::psq(c, Role::Pawn, a) ^ ZobristNumbers::psq(c, Role::Pawn, b);
+        assert_eq!(ZobristNumbers::cuckoo(key ^ ZobristNumbers::turn()), None);
+    }
 }
@@ -1,6 +1,6 @@
 #![cfg_attr(target_arch = "x86_64", feature(stdarch_x86_mm_shuffle))]
 #![feature(
-    array_chunks,
+    //SHIM array_chunks,
     coverage_attribute,
     new_zeroed_alloc,
     ptr_as_ref_unchecked,
@@ -14,6 +14,9 @@ pub mod chess;
 pub mod nnue;
 /// Minimax searching algorithm.
 pub mod search;
+/// Tuning of search parameters and the win-draw-loss model.
+#[cfg(any(feature = "spsa", feature = "wdl"))]
+pub mod tune;
 /// UCI protocol.
 pub mod uci;
 /// Assorted utilities.
@@ -1,4 +1,6 @@
-use crate::chess::{Color, Move, ParsePositionError, Perspective, Piece, Position, Role, Square};
+use crate::chess::{
+    Color, Move, ParsePositionError, Perspective, Piece, Position, Role, Square, Undo,
+};
 use crate::nnue::{Accumulator, Feature, Material, Nnue, Positional, Value};
 use crate::util::{Assume, Integer};
 use arrayvec::ArrayVec;
@@ -58,20 +60,49 @@ impl Evaluator {
 
     /// Play a [`Move`].
     pub fn play(&mut self, m: Move) {
+        self.make(m);
+    }
+
+    /// Play a [`Move`], returning the state needed to [unmake](Self::unmake) it.
+    pub fn make(&mut self, m: Move) -> Undo {
         let turn = self.turn();
+        let undo = self.pos.make(m);
+        self.update(turn, m, &undo, false);
+        undo
+    }
+
+    /// Take back a [`Move`] given the state returned by [`Evaluator::make`].
+    pub fn unmake(&mut self, m: Move, undo: Undo) {
+        let turn = !self.turn();
+        let (wc, wt) = (m.whence(), m.whither());
+        let role = undo.role();
+
+        self.update(turn, m, &undo, true);
+        self.pos.unmake(m, undo);
+
+        // The accumulator can only be refreshed once the king is back in place.
+        if is_refresh(turn, role, wc, wt) {
+            self.acc.refresh(turn);
+            for (p, s) in self.pos.iter() {
+                self.acc.add(turn, Feature::new(turn, wc, p, s));
+            }
+        }
+    }
+
+    /// Updates the accumulators given a [`Move`] played by `turn`, or reverts it.
+    fn update(&mut self, turn: Color, m: Move, undo: &Undo, revert: bool) {
         let promotion = m.promotion();
         let (wc, wt) = (m.whence(), m.whither());
-        let (role, capture) = self.pos.play(m);
+        let (role, capture) = (undo.role(), undo.capture());
         let mut sides = ArrayVec::<Color, 2>::from([!turn, turn]);
 
-        if role == Role::King
-            && Feature::new(turn, wc, Piece::lower(), Square::lower())
-                != Feature::new(turn, wt, Piece::lower(), Square::lower())
-        {
+        if is_refresh(turn, role, wc, wt) {
             sides.truncate(1);
-            self.acc.refresh(turn);
-            for (p, s) in self.pos.iter() {
-                self.acc.add(turn, Feature::new(turn, wt, p, s));
+            if !revert {
+                self.acc.refresh(turn);
+                for (p, s) in self.pos.iter() {
+                    self.acc.add(turn, Feature::new(turn, wt, p, s));
+                }
             }
         }
 
@@ -79,11 +110,20 @@ impl Evaluator {
             let ksq = self.king(side);
             let old = Feature::new(side, ksq, Piece::new(role, turn), wc);
             let new = Feature::new(side, ksq, Piece::new(promotion.unwrap_or(role), turn), wt);
-            self.acc.replace(side, old, new);
+
+            if revert {
+                self.acc.replace(side, new, old);
+            } else {
+                self.acc.replace(side, old, new);
+            }
 
             if let Some((r, sq)) = capture {
-                let victim = Piece::new(r, !turn);
-                self.acc.remove(side, Feature::new(side, ksq, victim, sq));
+                let victim = Feature::new(side, ksq, Piece::new(r, !turn), sq);
+                if revert {
+                    self.acc.add(side, victim);
+                } else {
+                    self.acc.remove(side, victim);
+                }
             } else if role == Role::King && (wt - wc).abs() == 2 {
                 let rook = Piece::new(Role::Rook, turn);
                 let (wc, wt) = if wt > wc {
@@ -94,7 +134,12 @@ impl Evaluator {
 
                 let old = Feature::new(side, ksq, rook, wc);
                 let new = Feature::new(side, ksq, rook, wt);
-                self.acc.replace(side, old, new);
+
+                if revert {
+                    self.acc.replace(side, new, old);
+                } else {
+                    self.acc.replace(side, old, new);
+                }
             }
         }
     }
@@ -157,6 +202,14 @@ impl Evaluator {
     }
 }
 
+/// Whether moving the king requires refreshing its side's accumulator.
+#[inline(always)]
+fn is_refresh(turn: Color, role: Role, wc: Square, wt: Square) -> bool {
+    role == Role::King
+        && Feature::new(turn, wc, Piece::lower(), Square::lower())
+            != Feature::new(turn, wt, Piece::lower(), Square::lower())
+}
+
 impl FromStr for Evaluator {
     type Err = ParsePositionError;
 
@@ -183,6 +236,32 @@ mod tests {
         assert_eq!(e, Evaluator::new(pos));
     }
 
+    #[proptest]
+    fn make_is_equivalent_to_play(
+        #[filter(#e.outcome().is_none())] mut e: Evaluator,
+        #[map(|sq: Selector| sq.select(#e.moves().flatten()))] m: Move,
+    ) {
+        let mut next = e.clone();
+        next.play(m);
+        e.make(m);
+        assert_eq!(e, next);
+        assert_eq!(e.zobrist(), next.zobrist());
+    }
+
+    #[proptest]
+    fn unmake_restores_evaluator(
+        #[filter(#e.outcome().is_none())] mut e: Evaluator,
+        #[map(|sq: Selector| sq.select(#e.moves().flatten()))] m: Move,
+    ) {
+        let prev = e.clone();
+        let undo = e.make(m);
+        e.unmake(m, undo);
+        assert_eq!(e, prev);
+        assert_eq!(e.zobrist(), prev.zobrist());
+        assert_eq!(e.checkers(), prev.checkers());
+        assert_eq!(e.pinned(), prev.pinned());
+    }
+
     #[proptest]
     fn pass_updates_evaluator(#[filter(!#e.is_check())] mut e: Evaluator) {
         let mut pos = e.pos.clone();
@@ -47,7 +47,9 @@ impl<const N: usize> Hidden<N> {
             (w.as_ptr() as usize % 32 == 0).assume();
             (i.as_ptr() as usize % 32 == 0).assume();
 
-            for (a, x) in Iterator::zip(w.array_chunks::<128>(), i.array_chunks::<128>()) {
+            for (a, x) in
+                Iterator::zip(w.as_chunks::<128>().0.iter(), i.as_chunks::<128>().0.iter())
+            {
                 let a = transmute::<&[i8; 128], &[__m256i; 4]>(a);
                 let x = transmute::<&[i16; 128], &[[__m256i; 2]; 4]>(x);
 
@@ -104,7 +106,8 @@ impl<const N: usize> Hidden<N> {
             (w.as_ptr() as usize % 16 == 0).assume();
             (i.as_ptr() as usize % 16 == 0).assume();
 
-            for (a, x) in Iterator::zip(w.array_chunks::<64>(), i.array_chunks::<64>()) {
+            for (a, x) in Iterator::zip(w.as_chunks::<64>().0.iter(), i.as_chunks::<64>().0.iter())
+            {
                 let a = transmute::<&[i8; 64], &[__m128i; 4]>(a);
                 let x = transmute::<&[i16; 64], &[[__m128i; 2]; 4]>(x);
 
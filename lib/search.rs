@@ -1,29 +1,49 @@
+mod captures;
 mod continuation;
 mod control;
+mod correction;
+mod counters;
 mod depth;
 mod engine;
 mod gravity;
+mod handle;
 mod history;
 mod killers;
 mod limits;
 mod line;
+mod mate;
 mod options;
+mod params;
 mod ply;
 mod pv;
 mod score;
+mod stats;
+mod time;
+mod trace;
 mod transposition;
+mod wdl;
 
+pub use captures::*;
 pub use continuation::*;
 pub use control::*;
+pub use correction::*;
+pub use counters::*;
 pub use depth::*;
 pub use engine::*;
 pub use gravity::*;
+pub use handle::*;
 pub use history::*;
 pub use killers::*;
 pub use limits::*;
 pub use line::*;
+pub use mate::*;
 pub use options::*;
+pub use params::*;
 pub use ply::*;
 pub use pv::*;
 pub use score::*;
+pub use stats::*;
+pub use time::*;
+pub use trace::*;
 pub use transposition::*;
+pub use wdl::*;
@@ -0,0 +1,53 @@
+use crate::chess::{Move, Position, Role};
+use crate::search::{Graviton, Gravity};
+use crate::util::Assume;
+use derive_more::Debug;
+
+/// [Historical statistics] about captures, indexed by the attacker, target and victim.
+///
+/// [Historical statistics]: https://www.chessprogramming.org/History_Heuristic
+#[derive(Debug)]
+#[debug("Captures")]
+pub struct Captures(Box<[[[Graviton; 6]; 64]; 12]>);
+
+impl Default for Captures {
+    #[inline(always)]
+    fn default() -> Self {
+        Self(unsafe { Box::new_zeroed().assume_init() })
+    }
+}
+
+impl Captures {
+    #[inline(always)]
+    fn graviton(&self, pos: &Position, m: Move) -> &Graviton {
+        let piece = pos[m.whence()].assume() as usize;
+        let victim = match pos[m.whither()] {
+            Some(p) => p.role(),
+            None if m.is_capture() => Role::Pawn,
+            None => Role::King,
+        };
+
+        &self.0[piece][m.whither() as usize][victim as usize]
+    }
+}
+
+impl Gravity for Captures {
+    type Bonus = <Graviton as Gravity>::Bonus;
+
+    #[inline(always)]
+    fn get(&self, pos: &Position, m: Move) -> Self::Bonus {
+        self.graviton(pos, m).get(pos, m)
+    }
+
+    #[inline(always)]
+    fn update(&self, pos: &Position, m: Move, bonus: Self::Bonus) {
+        self.graviton(pos, m).update(pos, m, bonus);
+    }
+
+    #[inline(always)]
+    fn age(&self) {
+        for g in self.0.iter().flatten().flatten() {
+            g.age();
+        }
+    }
+}
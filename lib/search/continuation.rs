@@ -28,6 +28,13 @@ impl Gravity for Reply {
         let piece = pos[m.whence()].assume() as usize;
         self.0[piece][m.whither() as usize].update(pos, m, bonus);
     }
+
+    #[inline(always)]
+    fn age(&self) {
+        for g in self.0.iter().flatten() {
+            g.age();
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -48,4 +55,12 @@ impl Continuation {
         let victim = pos[m.whither()].map_or(Role::King, |p| p.role()) as usize;
         &self.0[piece][m.whither() as usize][victim]
     }
+
+    /// Decays the accumulated history of every [`Reply`].
+    #[inline(always)]
+    pub fn age(&self) {
+        for r in self.0.iter().flatten().flatten() {
+            r.age();
+        }
+    }
 }
@@ -0,0 +1,100 @@
+use crate::chess::{Color, Position, Zobrist};
+use crate::nnue::Value;
+use crate::search::Depth;
+use crate::util::Integer;
+use derive_more::Debug;
+use std::sync::atomic::{AtomicI16, Ordering::Relaxed};
+
+/// [Correction history] of the static evaluation.
+///
+/// Learns the difference between the search score and the static evaluation
+/// keyed by the pawn structure and by the placement of the other pieces of each side.
+///
+/// [Correction history]: https://www.chessprogramming.org/Static_Evaluation_Correction_History
+#[derive(Debug)]
+#[debug("Correction")]
+pub struct Correction {
+    pawns: Box<[[AtomicI16; Self::SIZE]; 2]>,
+    pieces: Box<[[[AtomicI16; Self::SIZE]; 2]; 2]>,
+}
+
+impl Default for Correction {
+    #[inline(always)]
+    fn default() -> Self {
+        Correction {
+            pawns: unsafe { Box::new_zeroed().assume_init() },
+            pieces: unsafe { Box::new_zeroed().assume_init() },
+        }
+    }
+}
+
+impl Correction {
+    const SIZE: usize = 16384;
+    const GRAIN: i32 = 64;
+    const LIMIT: i32 = 128 * Self::GRAIN;
+
+    #[inline(always)]
+    fn index(key: Zobrist) -> usize {
+        key.cast::<usize>() % Self::SIZE
+    }
+
+    #[inline(always)]
+    fn entries(&self, pos: &Position) -> [&AtomicI16; 3] {
+        let turn = pos.turn() as usize;
+        let white = pos.piece_zobrist(Color::White);
+        let black = pos.piece_zobrist(Color::Black);
+
+        [
+            &self.pawns[turn][Self::index(pos.pawn_zobrist())],
+            &self.pieces[Color::White as usize][turn][Self::index(white)],
+            &self.pieces[Color::Black as usize][turn][Self::index(black)],
+        ]
+    }
+
+    /// Corrects the static evaluation of a [`Position`].
+    #[inline(always)]
+    pub fn correct(&self, pos: &Position, value: Value) -> Value {
+        let [pawns, white, black] = self.entries(pos).map(|e| e.load(Relaxed) as i32);
+        let correction = (2 * pawns + white + black) / (2 * Self::GRAIN);
+        (value.get() as i32 + correction).saturate()
+    }
+
+    /// Learns the difference between the search score and the static evaluation of a [`Position`].
+    #[inline(always)]
+    pub fn update(&self, pos: &Position, draft: Depth, diff: i32) {
+        let weight = draft.cast::<i32>().clamp(0, 15) + 1;
+        let target = diff.clamp(-Self::LIMIT, Self::LIMIT) * Self::GRAIN;
+
+        for entry in self.entries(pos) {
+            let old = entry.load(Relaxed) as i32;
+            let new = (old * (256 - weight) + target * weight) / 256;
+            entry.store(new.clamp(-Self::LIMIT, Self::LIMIT) as i16, Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Debug;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn correction_is_initially_zero(pos: Position, v: Value) {
+        assert_eq!(Correction::default().correct(&pos, v), v);
+    }
+
+    #[proptest]
+    fn update_moves_correction_towards_difference(
+        pos: Position,
+        #[filter(#v.get().abs() < 3000)] v: Value,
+        d: Depth,
+        #[strategy(-500i32..=500)] diff: i32,
+    ) {
+        let c = Correction::default();
+        c.update(&pos, d, diff);
+        let corrected = c.correct(&pos, v).get() as i32 - v.get() as i32;
+        assert!(corrected.abs() <= diff.abs());
+        assert!(corrected == 0 || corrected.signum() == diff.signum());
+    }
+}
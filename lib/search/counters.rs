@@ -0,0 +1,58 @@
+use crate::chess::{Move, Position};
+use crate::util::Assume;
+
+/// A table of [counter moves].
+///
+/// [counter moves]: https://www.chessprogramming.org/Countermove_Heuristic
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Counters(Box<[[Option<Move>; 64]; 12]>);
+
+impl Default for Counters {
+    #[inline(always)]
+    fn default() -> Self {
+        Counters(Box::new([[None; 64]; 12]))
+    }
+}
+
+impl Counters {
+    /// Replaces the counter move to the last move played in a [`Position`].
+    #[inline(always)]
+    pub fn insert(&mut self, pos: &Position, last: Move, m: Move) {
+        let piece = pos[last.whither()].assume() as usize;
+        self.0[piece][last.whither() as usize] = Some(m);
+    }
+
+    /// The counter move to the last move played in a [`Position`], if any.
+    #[inline(always)]
+    pub fn get(&self, pos: &Position, last: Move) -> Option<Move> {
+        let piece = pos[last.whither()].assume() as usize;
+        self.0[piece][last.whither() as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn get_returns_none_by_default(
+        pos: Position,
+        #[filter(#pos[#last.whither()].is_some())] last: Move,
+    ) {
+        assert_eq!(Counters::default().get(&pos, last), None);
+    }
+
+    #[proptest]
+    fn get_returns_move_inserted_most_recently(
+        pos: Position,
+        #[filter(#pos[#last.whither()].is_some())] last: Move,
+        m: Move,
+        n: Move,
+    ) {
+        let mut c = Counters::default();
+        c.insert(&pos, last, m);
+        c.insert(&pos, last, n);
+        assert_eq!(c.get(&pos, last), Some(n));
+    }
+}
@@ -1,41 +1,109 @@
-use crate::chess::{Move, Outcome, Position};
+use crate::chess::{Butterfly, Move, Outcome, Position};
 use crate::nnue::{Evaluator, Value};
 use crate::search::*;
-use crate::util::{Assume, Counter, Integer, Timer, Trigger};
+use crate::util::{Assume, Baton, Counter, Integer, Job, Pool, Timer, Trigger};
 use arrayvec::ArrayVec;
 use derive_more::Deref;
-use std::{mem::swap, ops::Range, thread, time::Duration};
+use futures::channel::{mpsc, oneshot};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use std::{mem, ops::Range};
+
+#[cfg(feature = "trace")]
+use crate::uci::UciMove;
 
 #[cfg(test)]
-use proptest::strategy::LazyJust;
+use proptest::strategy::{Just, LazyJust};
+
+/// The state shared by all search threads.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+struct Shared {
+    #[cfg_attr(test, map(|s: HashSize| Arc::new(TranspositionTable::new(s))))]
+    tt: Arc<TranspositionTable>,
+    #[cfg_attr(test, strategy(LazyJust::new(Arc::default)))]
+    history: Arc<History>,
+    #[cfg_attr(test, strategy(LazyJust::new(Arc::default)))]
+    captures: Arc<Captures>,
+    #[cfg_attr(test, strategy(LazyJust::new(Arc::default)))]
+    continuation: Arc<Continuation>,
+    #[cfg_attr(test, strategy(LazyJust::new(Arc::default)))]
+    correction: Arc<Correction>,
+    #[cfg_attr(test, strategy(LazyJust::new(Arc::default)))]
+    followup: Arc<Continuation>,
+    #[cfg_attr(test, strategy(Just(Params::default())))]
+    params: Params,
+}
+
+impl Shared {
+    /// Decays the history learned in previous searches.
+    fn age(&self) {
+        self.history.age();
+        self.captures.age();
+        self.continuation.age();
+        self.followup.age();
+    }
+}
+
+/// The state private to each search thread, preserved across searches.
+#[derive(Debug, Clone)]
+struct Local {
+    killers: [Killers; Ply::MAX as usize + 1],
+    counters: Counters,
+}
+
+impl Default for Local {
+    #[inline(always)]
+    fn default() -> Self {
+        Local {
+            killers: [Killers::default(); Ply::MAX as usize + 1],
+            counters: Counters::default(),
+        }
+    }
+}
 
 /// A chess engine.
 #[derive(Debug, Clone, Deref)]
-pub struct Search<'a> {
+struct Search<'a> {
     #[deref]
-    engine: &'a Engine,
+    shared: &'a Shared,
     ctrl: Control<'a>,
-    killers: [Killers; Ply::MAX as usize + 1],
+    baton: Option<&'a Baton>,
+    id: usize,
+    local: Local,
+    played: [Option<Move>; Ply::MAX as usize + 1],
     continuation: [Option<&'a Reply>; Ply::MAX as usize + 1],
+    followup: [Option<&'a Reply>; Ply::MAX as usize + 1],
+    excluded: [Option<Move>; Ply::MAX as usize + 1],
+    nodes: u64,
+    effort: Butterfly<u64>,
+    stats: Stats,
+    tracer: Tracer,
 }
 
 impl<'a> Search<'a> {
-    fn new(engine: &'a Engine, ctrl: Control<'a>) -> Self {
-        let killers = [Killers::default(); Ply::MAX as usize + 1];
-        let continuation = [None; Ply::MAX as usize + 1];
-
+    fn new(shared: &'a Shared, ctrl: Control<'a>) -> Self {
         Search {
-            engine,
+            shared,
             ctrl,
-            killers,
-            continuation,
+            baton: None,
+            id: 0,
+            local: Local::default(),
+            played: [None; Ply::MAX as usize + 1],
+            continuation: [None; Ply::MAX as usize + 1],
+            followup: [None; Ply::MAX as usize + 1],
+            excluded: [None; Ply::MAX as usize + 1],
+            nodes: 0,
+            effort: [[0; 64]; 64],
+            stats: Stats::default(),
+            tracer: Tracer::default(),
         }
     }
 
     #[allow(clippy::too_many_arguments)]
     fn record(
         &mut self,
-        pos: &Position,
+        pos: &Evaluator,
         moves: &[(Move, Value)],
         bounds: Range<Score>,
         depth: Depth,
@@ -45,30 +113,108 @@ impl<'a> Search<'a> {
     ) {
         let draft = depth - ply;
         if score >= bounds.end {
-            if best.is_quiet() {
-                self.killers[ply.cast::<usize>()].insert(best);
+            // Cutoffs in the quiescence search don't displace killers and counters.
+            if best.is_quiet() && draft > 0 {
+                self.local.killers[ply.cast::<usize>()].insert(best);
+                if let Some(last) = self.last(ply) {
+                    self.local.counters.insert(pos, last, best);
+                }
             }
 
-            self.history.update(pos, best, draft.get());
-
-            let counter = self.continuation.get(ply.cast::<usize>().wrapping_sub(1));
-            counter.update(pos, best, draft.get());
+            let bonus = self.bonus(draft);
+            self.update(pos, ply, best, bonus);
 
             for &(m, _) in moves.iter().rev() {
                 if m == best {
                     break;
                 } else {
-                    self.history.update(pos, m, -draft.get());
-
-                    let counter = self.continuation.get(ply.cast::<usize>().wrapping_sub(1));
-                    counter.update(pos, m, -draft.get())
+                    self.update(pos, ply, m, -bonus);
                 }
             }
         }
 
-        let score = ScoreBound::new(bounds, score, ply);
-        let tpos = Transposition::new(score, draft, best);
-        self.tt.set(pos.zobrist(), tpos);
+        // Only conclusive scores of quiet positions are learned by the correction history.
+        if draft > 0 && !pos.is_check() && best.is_quiet() && score.mate().is_none() {
+            let diff = score.get() as i32 - pos.evaluate().get() as i32;
+            if (score < bounds.end || diff > 0) && (score > bounds.start || diff < 0) {
+                self.correction.update(pos, draft, diff);
+            }
+        }
+
+        // Entries of draft 0 must not depend on whether quiet checks were searched.
+        if ply != depth {
+            let score = ScoreBound::new(bounds, score, ply);
+            let tpos = Transposition::new(score, draft, best);
+            self.tt.set(pos.zobrist(), tpos);
+        }
+    }
+
+    /// The entry pushed onto a stack `n` plies before `ply`, if any.
+    #[inline(always)]
+    fn ancestor<T: Copy>(stack: &[Option<T>], ply: Ply, n: usize) -> Option<T> {
+        let idx = ply.cast::<usize>().checked_sub(n)?;
+        *stack.get(idx)?
+    }
+
+    /// The move played just before `ply`, if any.
+    #[inline(always)]
+    fn last(&self, ply: Ply) -> Option<Move> {
+        Self::ancestor(&self.played, ply, 1)
+    }
+
+    /// Pushes the move searched at `ply` onto the stack, or [`None`] for the null move.
+    #[inline(always)]
+    fn push(&mut self, pos: &Position, ply: Ply, m: Option<Move>) {
+        let shared = self.shared;
+        let idx = ply.cast::<usize>();
+        self.played[idx] = m;
+        self.continuation[idx] = m.map(|m| shared.continuation.reply(pos, m));
+        self.followup[idx] = m.map(|m| shared.followup.reply(pos, m));
+    }
+
+    /// Rates a move at `ply` for the purposes of move ordering.
+    #[inline(always)]
+    fn rate(&self, pos: &Evaluator, ply: Ply, best: Option<Move>, m: Move) -> Value {
+        if Some(m) == best {
+            return Value::upper();
+        } else if self.local.killers[ply.cast::<usize>()].contains(m) {
+            return Value::new(128);
+        } else if self.last(ply).and_then(|l| self.local.counters.get(pos, l)) == Some(m) {
+            return Value::new(96);
+        }
+
+        let gain = if m.is_quiet() {
+            Value::new(0)
+        } else {
+            pos.gain(m)
+        };
+
+        gain + self.history(pos, ply, m)
+    }
+
+    /// The accumulated history of a move at `ply`.
+    #[inline(always)]
+    fn history(&self, pos: &Position, ply: Ply, m: Move) -> Value {
+        let counter = Self::ancestor(&self.continuation, ply, 1);
+        let followup = Self::ancestor(&self.followup, ply, 2);
+        let mut history = self.history.get(pos, m) as i32;
+        history += counter.get(pos, m) as i32 + followup.get(pos, m) as i32;
+        if !m.is_quiet() {
+            history += self.captures.get(pos, m) as i32;
+        }
+
+        (history / 128).saturate()
+    }
+
+    /// Updates the history of a move at `ply`.
+    #[inline(always)]
+    fn update(&self, pos: &Position, ply: Ply, m: Move, bonus: i16) {
+        self.history.update(pos, m, bonus);
+        Self::ancestor(&self.continuation, ply, 1).update(pos, m, bonus);
+        Self::ancestor(&self.followup, ply, 2).update(pos, m, bonus);
+        if !m.is_quiet() {
+            self.captures.update(pos, m, bonus);
+        }
     }
 
     /// An implementation of [mate distance pruning].
@@ -86,19 +232,40 @@ impl<'a> Search<'a> {
     fn nmp(&self, surplus: Score, draft: Depth) -> Option<Depth> {
         match surplus.get() {
             ..0 => None,
-            0.. => Some(draft - 3 - draft / 4),
+            0.. => Some(draft - self.params.nmp_reduction() - draft / self.params.nmp_divisor()),
         }
     }
 
-    /// An implementation of [multi-cut pruning].
+    /// An implementation of [singular extensions].
     ///
-    /// [multi-cut pruning]: https://www.chessprogramming.org/Multi-Cut
-    fn mcp(&self, surplus: Score, draft: Depth) -> Option<Depth> {
+    /// Returns the null-window bound and the depth of the verification search.
+    ///
+    /// [singular extensions]: https://www.chessprogramming.org/Singular_Extensions
+    fn se(&self, bound: ScoreBound, ply: Ply, draft: Depth) -> Option<(Score, Depth)> {
         match draft.get() {
             ..6 => None,
-            6.. => match surplus.get() {
-                ..0 => None,
-                0.. => Some(draft / 2),
+            6.. => match bound {
+                ScoreBound::Upper(_) => None,
+                _ if bound.lower(ply).mate().is_some() => None,
+                _ => {
+                    let margin = self.params.se_margin() * draft.cast::<i16>();
+                    Some((bound.lower(ply) - margin, (draft - 1) / 2))
+                }
+            },
+        }
+    }
+
+    /// An implementation of [ProbCut].
+    ///
+    /// Returns the raised bound and the depth of the verification search.
+    ///
+    /// [ProbCut]: https://www.chessprogramming.org/ProbCut
+    fn pc(&self, beta: Score, draft: Depth) -> Option<(Score, Depth)> {
+        match draft.get() {
+            ..5 => None,
+            5.. => match beta.mate() {
+                Some(_) => None,
+                None => Some((beta + self.params.pc_margin(), draft - 4)),
             },
         }
     }
@@ -109,8 +276,10 @@ impl<'a> Search<'a> {
     fn razor(&self, deficit: Score, draft: Depth) -> Option<Depth> {
         match deficit.get() {
             ..0 => None,
-            s @ 0..600 => Some(draft - (s + 30) / 210),
-            600.. => Some(draft - 3),
+            s @ 0.. => {
+                let (offset, divisor) = (self.params.razor_offset(), self.params.razor_divisor());
+                Some(draft - ((s + offset) / divisor).min(3))
+            }
         }
     }
 
@@ -120,8 +289,10 @@ impl<'a> Search<'a> {
     fn rfp(&self, surplus: Score, draft: Depth) -> Option<Depth> {
         match surplus.get() {
             ..0 => None,
-            s @ 0..360 => Some(draft - (s + 60) / 140),
-            360.. => Some(draft - 3),
+            s @ 0.. => {
+                let (offset, divisor) = (self.params.rfp_offset(), self.params.rfp_divisor());
+                Some(draft - ((s + offset) / divisor).min(3))
+            }
         }
     }
 
@@ -131,8 +302,30 @@ impl<'a> Search<'a> {
     fn fp(&self, deficit: Score, draft: Depth) -> Option<Depth> {
         match deficit.get() {
             ..0 => None,
-            d @ 0..90 => Some(draft - (d + 30) / 40),
-            90.. => Some(draft - 3),
+            d @ 0.. => {
+                let (offset, divisor) = (self.params.fp_offset(), self.params.fp_divisor());
+                Some(draft - ((d + offset) / divisor).min(3))
+            }
+        }
+    }
+
+    /// An implementation of [delta pruning].
+    ///
+    /// [delta pruning]: https://www.chessprogramming.org/Delta_Pruning
+    fn dp(&self, deficit: Score) -> bool {
+        deficit >= self.params.dp_margin()
+    }
+
+    /// An implementation of [late move pruning].
+    ///
+    /// Returns the number of moves after which late quiet moves are pruned.
+    ///
+    /// [late move pruning]: https://www.chessprogramming.org/Futility_Pruning#MoveCountBasedPruning
+    fn lmp(&self, draft: Depth) -> Option<usize> {
+        match draft.get() {
+            ..1 => None,
+            d @ 1..8 => Some(3 + (d as usize).pow(2)),
+            8.. => None,
         }
     }
 
@@ -140,7 +333,18 @@ impl<'a> Search<'a> {
     ///
     /// [late move reductions]: https://www.chessprogramming.org/Late_Move_Reductions
     fn lmr(&self, draft: Depth, idx: usize) -> i16 {
-        draft.get().max(1).ilog2() as i16 * idx.max(1).ilog2() as i16 / 3
+        let (d, i) = (draft.get().max(1).ilog2() as i16, idx.max(1).ilog2() as i16);
+        d * i * 100 / self.params.lmr_divisor()
+    }
+
+    /// The [history] bonus for a move that caused a cutoff at a given draft.
+    ///
+    /// Cutoffs in the quiescence search, where the draft is negative, earn no bonus.
+    ///
+    /// [history]: https://www.chessprogramming.org/History_Heuristic
+    fn bonus(&self, draft: Depth) -> i16 {
+        let d = draft.cast::<i32>().max(0);
+        (d * d * 32 + d * 64).min(<Graviton>::MAX as i32 / 8) as i16
     }
 
     /// The [alpha-beta] search.
@@ -153,11 +357,70 @@ impl<'a> Search<'a> {
         depth: Depth,
         ply: Ply,
     ) -> Result<Pv<N>, Interrupted> {
-        if ply.cast::<usize>() < N && depth > ply && bounds.start + 1 < bounds.end {
-            self.pvs(pos, bounds, depth, ply)
+        // Excluded moves are only honored by the full-width search.
+        let excluded = self.excluded.get(ply.cast::<usize>()).copied().flatten();
+        if depth <= ply && excluded.is_none() {
+            return Ok(self.qs(pos, bounds, depth, ply)?.truncate());
+        }
+
+        #[cfg(feature = "trace")]
+        let transposition = match excluded {
+            None => self.tt.get(pos.zobrist()),
+            Some(_) => None,
+        };
+
+        self.tracer.enter();
+        let pv = if ply.cast::<usize>() < N && bounds.start + 1 < bounds.end {
+            self.pvs(pos, bounds.clone(), depth, ply)?
         } else {
-            Ok(self.pvs::<0>(pos, bounds, depth, ply)?.truncate())
+            self.pvs::<0>(pos, bounds.clone(), depth, ply)?.truncate()
+        };
+
+        #[cfg(feature = "trace")]
+        self.trace(pos, bounds, depth, ply, transposition, pv.score());
+
+        Ok(pv)
+    }
+
+    /// Records a node visited by the full-width search into the trace, if any.
+    #[cfg(feature = "trace")]
+    fn trace(
+        &mut self,
+        pos: &Evaluator,
+        bounds: Range<Score>,
+        depth: Depth,
+        ply: Ply,
+        transposition: Option<Transposition>,
+        score: Score,
+    ) {
+        let decisions = self.tracer.leave();
+        if !self.tracer.is_recording(ply) {
+            return;
         }
+
+        let tt = transposition.map(|t| TracedTransposition {
+            score: match t.score() {
+                ScoreBound::Lower(_) => ScoreBound::Lower(t.score().bound(ply)),
+                ScoreBound::Upper(_) => ScoreBound::Upper(t.score().bound(ply)),
+                ScoreBound::Exact(_) => ScoreBound::Exact(t.score().bound(ply)),
+            },
+            draft: t.draft(),
+            best: t.transpose(ply).head().assume().into(),
+        });
+
+        let path = &self.played[..ply.cast::<usize>()];
+        self.tracer.record(TraceNode {
+            thread: self.id,
+            ply,
+            path: path.iter().map(|m| m.map(UciMove::from)).collect(),
+            alpha: bounds.start,
+            beta: bounds.end,
+            draft: depth - ply,
+            eval: pos.evaluate(),
+            tt,
+            decisions,
+            score,
+        });
     }
 
     /// The full-window alpha-beta search.
@@ -193,10 +456,21 @@ impl<'a> Search<'a> {
         depth: Depth,
         ply: Ply,
     ) -> Result<Pv<N>, Interrupted> {
+        let excluded = self
+            .excluded
+            .get_mut(ply.cast::<usize>())
+            .and_then(Option::take);
         self.ctrl.interrupted()?;
+        self.nodes += 1;
+        self.stats.count(Stat::Node);
         let is_root = ply == 0;
         (bounds.start < bounds.end).assume();
         let (alpha, beta) = match pos.outcome() {
+            // A repetition within the search tree is treated as a draw.
+            None if pos.repetition().is_some_and(|d| d < ply.cast()) => {
+                return Ok(Pv::empty(Score::new(0)));
+            }
+
             None => self.mdp(ply, &bounds),
             Some(Outcome::DrawByThreefoldRepetition) if is_root => self.mdp(ply, &bounds),
             Some(o) if o.is_draw() => return Ok(Pv::empty(Score::new(0))),
@@ -207,40 +481,74 @@ impl<'a> Search<'a> {
             return Ok(Pv::empty(alpha));
         }
 
-        let transposition = self.tt.get(pos.zobrist());
+        // The transposition may have been found by searching the excluded move.
+        let transposition = match excluded {
+            None => self.tt.get(pos.zobrist()),
+            Some(_) => None,
+        };
+
         let transposed = match transposition {
-            None => Pv::empty(pos.evaluate().saturate()),
+            None => {
+                let value = pos.evaluate();
+
+                #[cfg(not(test))]
+                // The correction history heuristic is not exact.
+                let value = self.correction.correct(pos, value);
+
+                Pv::empty(value.saturate())
+            }
             Some(t) => t.transpose(ply),
         };
 
         #[cfg(not(test))]
-        let mut depth = match transposition {
+        let depth = match transposition {
             #[cfg(not(test))]
             // The check extension heuristic is not exact.
-            Some(_) if !is_root && pos.is_check() => depth + 1,
+            Some(_) if !is_root && pos.is_check() => {
+                self.tracer.decide(Decision::CheckExtension);
+                depth + 1
+            }
 
             #[cfg(not(test))]
             // The internal iterative reduction heuristic is not exact.
-            None if !is_root && !pos.is_check() => depth - 2,
+            None if !is_root && !pos.is_check() && excluded.is_none() && depth - ply > 2 => {
+                self.tracer.decide(Decision::Iir);
+                depth - 2
+            }
 
             _ => depth,
         };
 
         let draft = depth - ply;
-        let quiesce = draft <= 0;
+
+        // The side to move can force a draw by repetition.
+        let alpha = match alpha < 0 && excluded.is_none() {
+            true if pos.upcoming_repetition().is_some_and(|i| i < ply.cast()) => Score::new(0),
+            _ => alpha,
+        };
+
+        if alpha >= beta {
+            return Ok(Pv::empty(alpha));
+        }
+
         let is_pv = alpha + 1 < beta;
         if let Some(t) = transposition {
+            self.stats.count(Stat::TtHit);
             let (lower, upper) = t.score().range(ply).into_inner();
 
             #[allow(clippy::collapsible_if)]
             if lower >= upper || upper <= alpha || lower >= beta {
                 if !is_pv && t.draft() >= draft {
+                    self.stats.count(Stat::TtCutoff);
+                    self.tracer.decide(Decision::TtCutoff);
                     return Ok(transposed.truncate());
                 }
             }
 
             if let Some(d) = self.razor(alpha - upper, draft) {
                 if !is_pv && t.draft() >= d {
+                    self.stats.count(Stat::RazorPrune);
+                    self.tracer.decide(Decision::Razor);
                     #[cfg(not(test))]
                     // The razoring heuristic is not exact.
                     return Ok(transposed.truncate());
@@ -249,6 +557,8 @@ impl<'a> Search<'a> {
 
             if let Some(d) = self.rfp(lower - beta, draft) {
                 if !is_pv && t.draft() >= d {
+                    self.stats.count(Stat::RfpPrune);
+                    self.tracer.decide(Decision::Rfp);
                     #[cfg(not(test))]
                     // The reverse futility pruning heuristic is not exact.
                     return Ok(transposed.truncate());
@@ -256,18 +566,13 @@ impl<'a> Search<'a> {
             }
         }
 
-        let alpha = match quiesce {
-            #[cfg(not(test))]
-            // The stand pat heuristic is not exact.
-            true => transposed.score().max(alpha),
-            _ => alpha,
-        };
-
-        if alpha >= beta || ply >= Ply::MAX {
+        if ply >= Ply::MAX {
             return Ok(transposed.truncate());
         } else if let Some(d) = self.nmp(transposed.score() - beta, draft) {
-            if !is_pv && !pos.is_check() && pos.pieces(pos.turn()).len() > 1 {
+            if !is_pv && !pos.is_check() && excluded.is_none() && pos.pieces(pos.turn()).len() > 1 {
                 if d <= 0 {
+                    self.stats.count(Stat::NmpPrune);
+                    self.tracer.decide(Decision::Nmp);
                     #[cfg(not(test))]
                     // The null move pruning heuristic is not exact.
                     return Ok(transposed.truncate());
@@ -275,8 +580,10 @@ impl<'a> Search<'a> {
                     let mut next = pos.clone();
                     next.pass();
                     self.tt.prefetch(next.zobrist());
-                    self.continuation[ply.cast::<usize>()] = None;
+                    self.push(pos, ply, None);
                     if -self.nw::<0>(&next, -beta + 1, d + ply, ply + 1)? >= beta {
+                        self.stats.count(Stat::NmpPrune);
+                        self.tracer.decide(Decision::Nmp);
                         #[cfg(not(test))]
                         // The null move pruning heuristic is not exact.
                         return Ok(transposed.truncate());
@@ -285,70 +592,118 @@ impl<'a> Search<'a> {
             }
         }
 
-        let killer = self.killers[ply.cast::<usize>()];
+        if let Some((b, d)) = self.pc(beta, draft) {
+            let refuted = transposition.is_some_and(|t| t.draft() >= d && t.score().upper(ply) < b);
+            if !is_pv && !pos.is_check() && excluded.is_none() && !refuted {
+                for m in pos.captures().flatten() {
+                    if transposed.score() + pos.gain(m) < b {
+                        continue;
+                    }
+
+                    let mut next = pos.clone();
+                    next.play(m);
+                    self.tt.prefetch(next.zobrist());
+                    self.push(pos, ply, Some(m));
+
+                    // The quiescence search quickly rules out captures that are unlikely to hold.
+                    let pv = match -self.nw::<0>(&next, -b + 1, (ply + 1).saturate(), ply + 1)? {
+                        pv if pv < b => pv,
+                        _ => -self.nw::<0>(&next, -b + 1, d + ply, ply + 1)?,
+                    };
+
+                    if pv >= b {
+                        self.stats.count(Stat::PcPrune);
+                        self.tracer.decide(Decision::ProbCut);
+                        #[cfg(not(test))]
+                        // The ProbCut heuristic is not exact.
+                        return Ok((m >> pv).truncate());
+                    }
+                }
+            }
+        }
+
         let mut moves: ArrayVec<_, 255> = pos
             .moves()
-            .filter(|ms| !quiesce || !ms.is_quiet())
             .flatten()
-            .map(|m| {
-                if Some(m) == transposed.head() {
-                    return (m, Value::upper());
-                } else if killer.contains(m) {
-                    return (m, Value::new(128));
-                }
-
-                let gain = if m.is_quiet() {
-                    Value::new(0)
-                } else {
-                    pos.gain(m)
-                };
-
-                let counter = self.continuation.get(ply.cast::<usize>().wrapping_sub(1));
-                (m, gain + self.history.get(pos, m) + counter.get(pos, m))
-            })
+            .map(|m| (m, self.rate(pos, ply, transposed.head(), m)))
             .collect();
 
+        moves.retain(|(m, _)| Some(*m) != excluded);
         moves.sort_unstable_by_key(|(_, gain)| *gain);
 
+        let mut singular = None;
         if let Some(t) = transposition {
-            if let Some(d) = self.mcp(t.score().lower(ply) - beta, draft) {
-                if !is_root && t.draft() >= d {
-                    for (m, _) in moves.iter().rev().skip(1) {
-                        let mut next = pos.clone();
-                        next.play(*m);
-                        self.tt.prefetch(next.zobrist());
-                        self.continuation[ply.cast::<usize>()] =
-                            Some(self.engine.continuation.reply(pos, *m));
-                        if -self.nw::<0>(&next, -beta + 1, d + ply, ply + 1)? >= beta {
-                            #[cfg(not(test))]
-                            // The multi-cut pruning heuristic is not exact.
+            if let Some((s, d)) = self.se(t.score(), ply, draft) {
+                let lower = t.score().lower(ply);
+                let best = transposed.head().filter(|&m| pos.is_legal(m));
+                if let Some(m) = best.filter(|_| !is_root && t.draft() + 3 >= draft) {
+                    self.excluded[ply.cast::<usize>()] = Some(m);
+                    let score = self.nw::<0>(pos, s, d + ply, ply)?.score();
+                    singular = match score {
+                        score if score < s && !is_pv && score + self.params.se_double() < s => {
+                            Some((m, 2))
+                        }
+                        score if score < s => Some((m, 1)),
+
+                        #[cfg(not(test))]
+                        // The multi-cut pruning heuristic is not exact.
+                        _ if s >= beta => {
+                            self.stats.count(Stat::McpPrune);
+                            self.tracer.decide(Decision::MultiCut);
                             return Ok(transposed.truncate());
                         }
-                    }
 
-                    #[cfg(not(test))]
-                    {
-                        // The singular extension heuristic is not exact.
-                        depth = depth + 1;
-                    }
+                        _ if lower >= beta => Some((m, -1)),
+                        _ => None,
+                    };
                 }
             }
         }
 
         let (mut head, mut tail) = match moves.pop() {
+            None if excluded.is_some() => return Ok(Pv::empty(alpha)),
             None => return Ok(transposed.truncate()),
             Some((m, _)) => {
                 let mut next = pos.clone();
                 next.play(m);
                 self.tt.prefetch(next.zobrist());
-                self.continuation[ply.cast::<usize>()] =
-                    Some(self.engine.continuation.reply(pos, m));
-                (m, -self.ab(&next, -beta..-alpha, depth, ply + 1)?)
+                self.push(pos, ply, Some(m));
+                let extension = match singular {
+                    #[cfg(not(test))]
+                    // The singular extension heuristic is not exact.
+                    Some((n, e)) if n == m => {
+                        self.tracer.decide(match e {
+                            2 => Decision::DoubleExtension,
+                            1 => Decision::SingularExtension,
+                            _ => Decision::NegativeExtension,
+                        });
+
+                        e
+                    }
+
+                    _ => 0,
+                };
+
+                let nodes = self.nodes;
+                let pv = -self.ab(&next, -beta..-alpha, depth + extension, ply + 1)?;
+                if is_root {
+                    self.effort[m.whence() as usize][m.whither() as usize] += self.nodes - nodes;
+                }
+
+                (m, pv)
             }
         };
 
+        if tail >= beta {
+            self.stats.count(Stat::Cutoff);
+            self.stats.count(Stat::FirstMoveCutoff);
+        }
+
         if tail >= beta || moves.is_empty() {
-            self.record(pos, &[], bounds, depth, ply, head, tail.score());
+            if excluded.is_none() {
+                self.record(pos, &[], bounds, depth, ply, head, tail.score());
+            }
+
             return Ok(head >> tail);
         }
 
@@ -358,13 +713,26 @@ impl<'a> Search<'a> {
                 s => s.max(alpha),
             };
 
+            if m.is_quiet() && !is_root && !pos.is_check() && !pos.gives_check(m) {
+                let losing = tail.score().mate().is_some_and(|p| p < 0);
+                if !losing && self.lmp(draft).is_some_and(|n| idx >= n) {
+                    self.stats.count(Stat::LmpPrune);
+                    self.tracer.decide(Decision::Lmp);
+                    #[cfg(not(test))]
+                    // The late move pruning heuristic is not exact.
+                    continue;
+                }
+            }
+
             let mut next = pos.clone();
             next.play(m);
 
             self.tt.prefetch(next.zobrist());
-            if gain < 0 && draft < 4 && !pos.is_check() && !next.is_check() {
-                let deficit = alpha + next.evaluate();
+            if gain < 0 && draft < 4 && !pos.is_check() && !pos.gives_check(m) {
+                let deficit = alpha + self.correction.correct(&next, next.evaluate());
                 if self.fp(deficit, draft).is_some_and(|d| d <= 0) {
+                    self.stats.count(Stat::FpPrune);
+                    self.tracer.decide(Decision::Fp);
                     #[cfg(not(test))]
                     // The futility pruning heuristic is not exact.
                     break;
@@ -378,105 +746,421 @@ impl<'a> Search<'a> {
                 _ => 0,
             };
 
-            self.continuation[ply.cast::<usize>()] = Some(self.engine.continuation.reply(pos, m));
+            if lmr > 0 {
+                self.tracer.decide(Decision::Lmr);
+            }
+
+            self.push(pos, ply, Some(m));
+            let nodes = self.nodes;
             let partial = match -self.nw(&next, -alpha, depth - lmr, ply + 1)? {
                 partial if partial <= alpha || (partial >= beta && lmr <= 0) => partial,
-                _ => -self.ab(&next, -beta..-alpha, depth, ply + 1)?,
+                _ => {
+                    if lmr > 0 {
+                        self.stats.count(Stat::LmrResearch);
+                        self.tracer.decide(Decision::Research);
+                    }
+
+                    -self.ab(&next, -beta..-alpha, depth, ply + 1)?
+                }
             };
 
+            if is_root {
+                self.effort[m.whence() as usize][m.whither() as usize] += self.nodes - nodes;
+            }
+
             if partial > tail {
                 (head, tail) = (m, partial);
             }
         }
 
-        self.record(pos, &moves, bounds, depth, ply, head, tail.score());
+        if tail >= beta {
+            self.stats.count(Stat::Cutoff);
+        }
+
+        if excluded.is_none() {
+            self.record(pos, &moves, bounds, depth, ply, head, tail.score());
+        }
+
         Ok(head >> tail)
     }
 
-    /// An implementation of [aspiration windows] with [iterative deepening].
+    /// The [quiescence] search.
     ///
-    /// [aspiration windows]: https://www.chessprogramming.org/Aspiration_Windows
-    /// [iterative deepening]: https://www.chessprogramming.org/Iterative_Deepening
-    fn aw<const N: usize>(
+    /// Only captures and promotions are searched, as well as quiet checks at the first ply,
+    /// unless the side to move is in check, in which case every evasion is searched.
+    ///
+    /// [quiescence]: https://www.chessprogramming.org/Quiescence_Search
+    fn qs(
         &mut self,
         pos: &Evaluator,
-        limit: Depth,
-        time: Range<Duration>,
-    ) -> Pv<N> {
-        let mut ctrl = Control::Unlimited;
-        swap(&mut self.ctrl, &mut ctrl);
-        self.fw::<0>(pos, Depth::new(0), Ply::new(0)).assume();
-        let mut pv = self.fw(pos, Depth::new(1), Ply::new(0)).assume();
-        swap(&mut self.ctrl, &mut ctrl);
+        bounds: Range<Score>,
+        depth: Depth,
+        ply: Ply,
+    ) -> Result<Pv<0>, Interrupted> {
+        self.ctrl.interrupted()?;
+        self.nodes += 1;
+        self.stats.count(Stat::QsNode);
+        (bounds.start < bounds.end).assume();
+        let (alpha, beta) = match pos.outcome() {
+            // A repetition within the search tree is treated as a draw.
+            None if pos.repetition().is_some_and(|d| d < ply.cast()) => {
+                return Ok(Pv::empty(Score::new(0)));
+            }
 
-        let mut depth = Depth::new(1);
-        'id: while depth < limit {
-            depth = depth + 1;
-            let mut draft = depth;
-            let mut delta = 5i16;
+            None => self.mdp(ply, &bounds),
+            Some(Outcome::DrawByThreefoldRepetition) if ply == 0 => self.mdp(ply, &bounds),
+            Some(o) if o.is_draw() => return Ok(Pv::empty(Score::new(0))),
+            Some(_) => return Ok(Pv::empty(Score::mated(ply))),
+        };
 
-            let (mut lower, mut upper) = match depth.get() {
-                ..=4 => (Score::lower(), Score::upper()),
-                _ => (pv.score() - delta, pv.score() + delta),
-            };
+        if alpha >= beta {
+            return Ok(Pv::empty(alpha));
+        }
 
-            'aw: loop {
-                delta = delta.saturating_mul(2);
-                if self.ctrl.timer().remaining() < Some(time.end - time.start) {
-                    break 'id;
-                }
+        let transposition = self.tt.get(pos.zobrist());
+        let transposed = match transposition {
+            None => {
+                let value = pos.evaluate();
 
-                let Ok(partial) = self.ab(pos, lower..upper, draft, Ply::new(0)) else {
-                    break 'id;
-                };
+                #[cfg(not(test))]
+                // The correction history heuristic is not exact.
+                let value = self.correction.correct(pos, value);
 
-                match partial.score() {
-                    score if (-lower..Score::upper()).contains(&-score) => {
-                        upper = lower / 2 + upper / 2;
-                        lower = score - delta;
-                        draft = depth;
-                    }
+                Pv::empty(value.saturate())
+            }
+            Some(t) => t.transpose(ply),
+        };
 
-                    score if (upper..Score::upper()).contains(&score) => {
-                        upper = score + delta;
-                        pv = partial;
+        // Quiet checks are only searched at the first ply of the quiescence search.
+        let checks = ply == depth;
 
-                        #[cfg(not(test))]
-                        {
-                            // Reductions are not exact.
-                            draft = draft - 1;
-                        }
-                    }
+        // Every transposition is deep enough, unless quiet checks were not searched.
+        let is_pv = alpha + 1 < beta;
+        if let Some(t) = transposition {
+            self.stats.count(Stat::TtHit);
+            let (lower, upper) = t.score().range(ply).into_inner();
 
-                    _ => {
-                        pv = partial;
-                        break 'aw;
-                    }
+            #[allow(clippy::collapsible_if)]
+            if lower >= upper || upper <= alpha || lower >= beta {
+                if !is_pv && (t.draft() > 0 || !checks) {
+                    self.stats.count(Stat::TtCutoff);
+                    return Ok(transposed.truncate());
                 }
             }
         }
 
-        pv
-    }
-}
+        let is_check = pos.is_check();
+        let alpha = match is_check {
+            #[cfg(not(test))]
+            // The stand pat heuristic is not exact.
+            false => transposed.score().max(alpha),
+            _ => alpha,
+        };
 
-/// A chess engine.
-#[derive(Debug)]
-#[cfg_attr(test, derive(test_strategy::Arbitrary))]
-pub struct Engine {
-    threads: ThreadCount,
-    #[cfg_attr(test, map(|s: HashSize| TranspositionTable::new(s)))]
-    tt: TranspositionTable,
-    #[cfg_attr(test, strategy(LazyJust::new(History::default)))]
-    history: History,
-    #[cfg_attr(test, strategy(LazyJust::new(Continuation::default)))]
-    continuation: Continuation,
-}
+        if alpha >= beta || ply >= Ply::MAX {
+            return Ok(transposed.truncate());
+        }
 
-impl Default for Engine {
-    fn default() -> Self {
-        Self::new()
-    }
+        let rate = |m: Move| (m, self.rate(pos, ply, transposed.head(), m));
+        let mut moves: ArrayVec<_, 255> = match is_check {
+            true => pos.moves().flatten().map(rate).collect(),
+            false => {
+                let checks = checks.then(|| pos.checks()).into_iter().flatten();
+                pos.captures().chain(checks).flatten().map(rate).collect()
+            }
+        };
+
+        if moves.is_empty() {
+            return Ok(transposed.truncate());
+        }
+
+        moves.sort_unstable_by_key(|(_, rating)| *rating);
+
+        let mut head = None;
+        let mut tail = match is_check {
+            #[cfg(not(test))]
+            // The stand pat heuristic is not exact.
+            false => transposed.truncate(),
+            _ => Pv::empty(Score::lower()),
+        };
+
+        for &(m, _) in moves.iter().rev() {
+            let alpha = match tail.score() {
+                s if s >= beta => break,
+                s => s.max(alpha),
+            };
+
+            if !is_check && !pos.see(m, 0) {
+                #[cfg(not(test))]
+                // The SEE pruning heuristic is not exact.
+                continue;
+            }
+
+            let mut next = pos.clone();
+            next.play(m);
+
+            self.tt.prefetch(next.zobrist());
+            if !is_check && !m.is_promotion() && !pos.gives_check(m) {
+                let deficit = alpha + self.correction.correct(&next, next.evaluate());
+                if self.dp(deficit) {
+                    #[cfg(not(test))]
+                    // The delta pruning heuristic is not exact.
+                    continue;
+                }
+            }
+
+            self.push(pos, ply, Some(m));
+            let partial = -self.qs(&next, -beta..-alpha, depth, ply + 1)?;
+            if partial > tail {
+                (head, tail) = (Some(m), partial);
+            }
+        }
+
+        let Some(head) = head else {
+            return Ok(tail);
+        };
+
+        self.record(pos, &moves, bounds, depth, ply, head, tail.score());
+        Ok(head >> tail)
+    }
+
+    /// The fraction of all nodes searched so far that were spent on a root move.
+    fn effort(&self, m: Option<Move>) -> f64 {
+        match m {
+            Some(m) if self.nodes > 0 => {
+                let nodes = self.effort[m.whence() as usize][m.whither() as usize];
+                nodes as f64 / self.nodes as f64
+            }
+
+            _ => 0.,
+        }
+    }
+
+    /// Whether to skip an iteration, staggering [helper threads] across depths.
+    ///
+    /// [helper threads]: https://www.chessprogramming.org/Lazy_SMP
+    fn skip(&self, depth: Depth) -> bool {
+        const SIZE: [i8; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+        const PHASE: [i8; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+        match self.id {
+            0 => false,
+            id => {
+                let i = (id - 1) % SIZE.len();
+                (depth.get() + PHASE[i]) / SIZE[i] % 2 != 0
+            }
+        }
+    }
+
+    /// An implementation of [aspiration windows] with [iterative deepening].
+    ///
+    /// [aspiration windows]: https://www.chessprogramming.org/Aspiration_Windows
+    /// [iterative deepening]: https://www.chessprogramming.org/Iterative_Deepening
+    fn aw<const N: usize>(
+        &mut self,
+        pos: &Evaluator,
+        limit: Depth,
+        time: Range<Duration>,
+        mut progress: impl FnMut(Depth, &Pv<N>),
+    ) -> Pv<N> {
+        let mut ctrl = Control::Unlimited;
+        mem::swap(&mut self.ctrl, &mut ctrl);
+        self.fw::<0>(pos, Depth::new(0), Ply::new(0)).assume();
+        let mut pv = self.fw(pos, Depth::new(1), Ply::new(0)).assume();
+        mem::swap(&mut self.ctrl, &mut ctrl);
+        progress(Depth::new(1), &pv);
+
+        let mut tm = TimeManager::new(time.clone());
+        tm.update(&pv, self.effort(pv.iter().next()));
+
+        let mut depth = Depth::new(1);
+        'id: while depth < limit {
+            depth = depth + 1;
+            if self.skip(depth) {
+                continue;
+            }
+
+            // Threads take turns at iteration boundaries in deterministic mode.
+            if let Some(baton) = self.baton {
+                baton.pass(self.id);
+            }
+
+            let mut draft = depth;
+            let mut delta = self.params.aw_delta();
+
+            let (mut lower, mut upper) = match depth.get() {
+                ..=4 => (Score::lower(), Score::upper()),
+                _ => (pv.score() - delta, pv.score() + delta),
+            };
+
+            'aw: loop {
+                delta = delta.saturating_mul(2);
+                let remaining = self.ctrl.timer().remaining().unwrap_or_default();
+                if time.end.saturating_sub(remaining) >= tm.limit() {
+                    break 'id;
+                }
+
+                let Ok(partial) = self.ab(pos, lower..upper, draft, Ply::new(0)) else {
+                    break 'id;
+                };
+
+                match partial.score() {
+                    score if (-lower..Score::upper()).contains(&-score) => {
+                        upper = lower / 2 + upper / 2;
+                        lower = score - delta;
+                        draft = depth;
+                    }
+
+                    score if (upper..Score::upper()).contains(&score) => {
+                        upper = score + delta;
+                        pv = partial;
+
+                        #[cfg(not(test))]
+                        {
+                            // Reductions are not exact.
+                            draft = draft - 1;
+                        }
+                    }
+
+                    _ => {
+                        pv = partial;
+                        break 'aw;
+                    }
+                }
+            }
+
+            progress(depth, &pv);
+            tm.update(&pv, self.effort(pv.iter().next()));
+        }
+
+        pv
+    }
+}
+
+/// The context of a search in progress, shared by all threads taking part in it.
+#[derive(Debug)]
+struct Context {
+    shared: Shared,
+    pos: Evaluator,
+    limits: Limits,
+    time: Range<Duration>,
+    nodes: Vec<Counter>,
+    timer: Timer,
+    stopper: Arc<Trigger>,
+    baton: Option<Baton>,
+    #[cfg(feature = "stats")]
+    stats: Arc<Mutex<Stats>>,
+    #[cfg(feature = "trace")]
+    trace: Option<Trace>,
+}
+
+impl Context {
+    /// Searches on behalf of the `id`-th thread, returning its last completed iteration.
+    fn search(
+        &self,
+        id: usize,
+        local: &mut Local,
+        mut progress: impl FnMut(Depth, &Pv),
+    ) -> (Depth, Pv) {
+        let nodes = &self.nodes[id % self.nodes.len()];
+        let ctrl = Control::Limited(nodes, &self.timer, &self.stopper);
+        let mut search = Search {
+            baton: self.baton.as_ref(),
+            id,
+            local: mem::take(local),
+            #[cfg(feature = "trace")]
+            tracer: Tracer::new(self.trace.clone()),
+            ..Search::new(&self.shared, ctrl)
+        };
+
+        if let Some(baton) = &self.baton {
+            baton.wait(id);
+        }
+
+        let mut ballot = (Depth::new(0), Pv::empty(Score::new(0)));
+        search.aw(
+            &self.pos,
+            self.limits.depth(),
+            self.time.clone(),
+            |d, pv| {
+                progress(d, pv);
+                ballot = (d, pv.clone());
+            },
+        );
+
+        *local = search.local;
+
+        if let Some(baton) = &self.baton {
+            // The helpers are stopped while waiting for their turn, so they stop deterministically.
+            if id == 0 {
+                self.stopper.disarm();
+            }
+
+            baton.leave(id);
+        }
+
+        #[cfg(feature = "stats")]
+        {
+            *self.stats.lock().unwrap_or_else(PoisonError::into_inner) += search.stats;
+        }
+
+        // Failing to write the trace must not interfere with the search.
+        #[cfg(feature = "trace")]
+        search.tracer.flush().ok();
+
+        ballot
+    }
+}
+
+/// Chooses the [`Pv`] by voting among the last iterations completed by each thread.
+///
+/// Every thread votes for its best move, weighted by the depth it completed
+/// and by how much its score exceeds the worst score among all threads.
+/// Threads that completed no iteration at all don't get to vote.
+fn vote(ballots: &[(Depth, Pv)]) -> Option<&(Depth, Pv)> {
+    let ballots = Vec::from_iter(ballots.iter().filter(|(d, _)| *d > 0));
+    let worst = ballots.iter().map(|(_, pv)| pv.score()).min()?;
+    let weight = |(d, pv): &(Depth, Pv)| {
+        let surplus = pv.score().get() as i64 - worst.get() as i64;
+        (surplus + 14) * d.get() as i64
+    };
+
+    let votes = |m: Option<Move>| -> i64 {
+        let ballots = ballots.iter().filter(|(_, pv)| pv.head() == m);
+        ballots.map(|&b| weight(b)).sum()
+    };
+
+    // Ties are broken in favor of the ballots cast first.
+    let ballots = ballots.iter().rev();
+    ballots
+        .max_by_key(|(d, pv)| (votes(pv.head()), *d, pv.score()))
+        .copied()
+}
+
+/// A chess engine.
+#[derive(Debug)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct Engine {
+    threads: ThreadCount,
+    overhead: Duration,
+    deterministic: bool,
+    shared: Shared,
+    #[cfg_attr(test, strategy(LazyJust::new(Pool::default)))]
+    pool: Pool<Local>,
+    #[cfg_attr(test, strategy(LazyJust::new(|| Mutex::new(Arc::new(Trigger::disarmed())))))]
+    stopper: Mutex<Arc<Trigger>>,
+    #[cfg(feature = "stats")]
+    #[cfg_attr(test, strategy(LazyJust::new(Arc::default)))]
+    stats: Arc<Mutex<Stats>>,
+    #[cfg(feature = "trace")]
+    #[cfg_attr(test, strategy(Just(None)))]
+    trace: Option<Trace>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Engine {
@@ -489,50 +1173,263 @@ impl Engine {
     pub fn with_options(options: &Options) -> Self {
         Engine {
             threads: options.threads,
-            tt: TranspositionTable::new(options.hash),
-            history: History::default(),
-            continuation: Continuation::default(),
+            overhead: options.overhead,
+            deterministic: options.deterministic,
+            shared: Shared {
+                tt: Arc::new(TranspositionTable::new(options.hash)),
+                history: Arc::default(),
+                captures: Arc::default(),
+                continuation: Arc::default(),
+                correction: Arc::default(),
+                followup: Arc::default(),
+                params: options.params,
+            },
+            pool: Pool::default(),
+            stopper: Mutex::new(Arc::new(Trigger::disarmed())),
+            #[cfg(feature = "stats")]
+            stats: Arc::default(),
+            #[cfg(feature = "trace")]
+            trace: None,
+        }
+    }
+
+    /// Resizes the transposition table to at most `size` many bytes.
+    ///
+    /// Existing transpositions are rehashed into the new table where possible.
+    /// A search still running in the background is stopped first.
+    pub fn set_hash(&mut self, size: HashSize) {
+        self.halt();
+        let tt = Arc::get_mut(&mut self.shared.tt);
+        tt.assume().resize(size, self.threads);
+    }
+
+    /// Changes the number of threads to use while searching.
+    ///
+    /// Threads that are kept retain whatever they learned in previous searches.
+    pub fn set_threads(&mut self, threads: ThreadCount) {
+        self.threads = threads;
+        self.pool.truncate(threads.cast());
+    }
+
+    /// Changes the time reserved on every move to compensate for communication latency.
+    pub fn set_overhead(&mut self, overhead: Duration) {
+        self.overhead = overhead;
+    }
+
+    /// Changes whether searches limited by nodes are reproducible for a given number of threads.
+    ///
+    /// In deterministic mode, the node budget is evenly split among threads,
+    /// which take turns searching one iteration at a time.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    /// Changes the tunable search parameters.
+    pub fn set_params(&mut self, params: Params) {
+        self.shared.params = params;
+    }
+
+    /// Changes where the search tree is recorded, if anywhere.
+    #[cfg(feature = "trace")]
+    pub fn set_trace(&mut self, trace: Option<Trace>) {
+        self.trace = trace;
+    }
+
+    /// The statistics of the last search.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        *self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Discards everything learned so far, including transpositions and history.
+    ///
+    /// A search still running in the background is stopped first.
+    pub fn clear(&mut self) {
+        self.halt();
+        let tt = Arc::get_mut(&mut self.shared.tt);
+        tt.assume().clear(self.threads);
+
+        self.shared.history = Arc::default();
+        self.shared.captures = Arc::default();
+        self.shared.continuation = Arc::default();
+        self.shared.correction = Arc::default();
+        self.shared.followup = Arc::default();
+        self.pool = Pool::default();
+    }
+
+    /// Stops the search running in the background, if any, and waits for it to finish.
+    ///
+    /// Once halted, no worker holds on to the shared state any longer.
+    fn halt(&mut self) {
+        let stopper = self.stopper.get_mut();
+        stopper.unwrap_or_else(PoisonError::into_inner).disarm();
+
+        // Jobs handed to the same worker run in order and the main thread
+        // only finishes once every helper has released the shared state.
+        if !self.pool.is_empty() {
+            let (done, finished) = std::sync::mpsc::channel::<()>();
+            let job = Box::new(move |_: &mut Local| drop(done)) as Job<Local>;
+            self.pool.execute([job]);
+            finished.recv().ok();
         }
     }
 
     fn time_to_search(&self, pos: &Position, limits: &Limits) -> Range<Duration> {
-        let (clock, inc) = match limits {
-            Limits::Clock(c, i) => (c, i),
+        let (clock, inc, movestogo) = match limits {
+            Limits::Clock(c, i, m) => (c.saturating_sub(self.overhead), *i, *m),
             _ => return limits.time()..limits.time(),
         };
 
-        let time_left = clock.saturating_sub(*inc);
-        let moves_left = 280 / pos.fullmoves().get().min(40);
+        let time_left = clock.saturating_sub(inc);
+        let moves_left = match movestogo {
+            Some(n) => n.clamp(1, 40),
+            None => 280 / pos.fullmoves().get().min(40),
+        };
+
         let time_per_move = inc.saturating_add(time_left / moves_left);
-        time_per_move / 2..time_per_move
+        let hard = time_per_move.saturating_mul(2).min(clock - clock / 5);
+        (time_per_move / 2).min(hard)..hard
     }
 
-    /// Searches for the [principal variation][`Pv`].
-    pub fn search(&self, pos: &Evaluator, limits: &Limits, stopper: &Trigger) -> Pv {
+    /// Searches for the [principal variation][`Pv`], blocking until the search finishes.
+    pub fn search(&self, pos: &Evaluator, limits: &Limits) -> Pv {
+        self.start(pos, limits).wait()
+    }
+
+    /// Starts searching for the [principal variation][`Pv`] in the background.
+    ///
+    /// The search runs on the engine's pool of worker threads,
+    /// which are woken up for the search and parked again once it finishes.
+    pub fn start(&self, pos: &Evaluator, limits: &Limits) -> SearchHandle {
+        let start = Instant::now();
         let time = self.time_to_search(pos, limits);
-        let nodes = Counter::new(limits.nodes());
-        let timer = Timer::new(time.end);
-        let ctrl = Control::Limited(&nodes, &timer, stopper);
-        let mut search = Search::new(self, ctrl);
+        let stopper = Arc::new(Trigger::armed());
+        *self.stopper.lock().unwrap_or_else(PoisonError::into_inner) = stopper.clone();
+        let (pv_tx, pv_rx) = oneshot::channel();
+        let (progress_tx, progress_rx) = mpsc::unbounded();
+        let (done, finished) = std::sync::mpsc::channel();
+
+        #[cfg(feature = "stats")]
+        {
+            *self.stats.lock().unwrap_or_else(PoisonError::into_inner) = Stats::default();
+        }
 
-        thread::scope(|s| {
-            for _ in 1..self.threads.get() {
-                let time = time.clone();
-                let mut search = search.clone();
-                s.spawn(move || search.aw::<0>(pos, limits.depth(), time));
+        let threads = self.threads.get();
+        let (nodes, baton) = match self.deterministic {
+            false => (vec![Counter::new(limits.nodes())], None),
+            true => {
+                // The main thread also gets the nodes that can't be evenly split.
+                let budget = limits.nodes() / threads as u64;
+                let remainder = limits.nodes() % threads as u64;
+                let nodes = (0..threads).map(|id| match id {
+                    0 => budget + remainder,
+                    _ => budget,
+                });
+
+                (nodes.map(Counter::new).collect(), Some(Baton::new(threads)))
             }
+        };
+
+        let ctx = Arc::new(Context {
+            shared: self.shared.clone(),
+            pos: pos.clone(),
+            limits: limits.clone(),
+            nodes,
+            timer: Timer::new(time.end),
+            time,
+            stopper: stopper.clone(),
+            baton,
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+            #[cfg(feature = "trace")]
+            trace: self.trace.clone(),
+        });
+
+        let helpers = Vec::from_iter((1..threads).map(|id| {
+            let (ctx, done) = (ctx.clone(), done.clone());
+            Box::new(move |local: &mut Local| {
+                let ballot = ctx.search(id, local, |_, _| {});
+                drop(ctx);
+                done.send((id, ballot)).ok();
+            }) as Job<Local>
+        }));
+
+        let main = Box::new(move |local: &mut Local| {
+            let ballot = ctx.search(0, local, |depth, pv| {
+                let remaining = ctx.nodes.iter().map(Counter::remaining).sum::<u64>();
+                let visited = ctx.limits.nodes() - remaining;
+                let progress = Progress::new(depth, start.elapsed(), visited, pv.clone());
+                progress_tx.unbounded_send(progress).ok();
+            });
+
+            // Wait for the helpers to release the shared state before reporting the result.
+            ctx.stopper.disarm();
+            drop(done);
+
+            // Ballots are sorted by thread, so ties are broken regardless of timing.
+            let mut ballots = Vec::from_iter([(0, ballot)].into_iter().chain(finished.iter()));
+            ballots.sort_unstable_by_key(|&(id, _)| id);
+            let ballots = Vec::from_iter(ballots.into_iter().map(|(_, b)| b));
+            let shared = ctx.shared.clone();
+            drop(ctx);
+
+            let (_, pv) = match vote(&ballots) {
+                #[cfg(not(test))]
+                // The voting heuristic is not exact.
+                Some(ballot) => ballot.clone(),
+                _ => ballots.into_iter().next().assume(),
+            };
+
+            pv_tx.send(pv).ok();
+
+            // Histories are aged off the clock, once the result has been reported.
+            shared.age();
+        }) as Job<Local>;
+
+        self.pool.execute([main].into_iter().chain(helpers));
+        SearchHandle::new(stopper, pv_rx, progress_rx)
+    }
+
+    /// Starts proving that the side to move mates in at most `moves` moves in the background.
+    ///
+    /// The [`MateSolver`] runs on one of the engine's worker threads within the given [`Limits`],
+    /// spending no more than the time a regular search would be expected to take,
+    /// and with a transposition store no larger than the transposition table.
+    /// The handle resolves to the outcome of [`MateSolver::solve`] and the number of nodes visited.
+    pub fn solve(
+        &self,
+        pos: &Position,
+        moves: u32,
+        limits: &Limits,
+    ) -> SearchHandle<(Result<Option<MateSolution>, Interrupted>, u64)> {
+        let time = self.time_to_search(pos, limits);
+        let stopper = Arc::new(Trigger::armed());
+        *self.stopper.lock().unwrap_or_else(PoisonError::into_inner) = stopper.clone();
+        let (result_tx, result_rx) = oneshot::channel();
+        let (_, progress_rx) = mpsc::unbounded();
 
-            let pv = search.aw(pos, limits.depth(), time);
-            stopper.disarm();
-            pv
-        })
+        let (pos, size) = (pos.clone(), self.shared.tt.size());
+        let nodes = Counter::new(limits.nodes());
+        let timer = Timer::new(time.start);
+        let trigger = stopper.clone();
+
+        let job = Box::new(move |_: &mut Local| {
+            let mut solver = MateSolver::new(size);
+            let ctrl = Control::Limited(&nodes, &timer, &trigger);
+            let result = solver.solve(&pos, moves, ctrl);
+            result_tx.send((result, solver.nodes())).ok();
+        }) as Job<Local>;
+
+        self.pool.execute([job]);
+        SearchHandle::new(stopper, result_rx, progress_rx)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use proptest::{prop_assume, sample::Selector};
+    use futures::{executor::block_on, StreamExt};
+    use proptest::{collection::vec, prelude::*, sample::Selector};
     use test_strategy::proptest;
 
     fn alphabeta(pos: &Evaluator, bounds: Range<Score>, depth: Depth, ply: Ply) -> Score {
@@ -540,6 +1437,7 @@ mod tests {
         debug_assert!(alpha < beta);
 
         let score = match pos.outcome() {
+            None if pos.repetition().is_some_and(|d| d < ply.cast()) => return Score::new(0),
             Some(o) if o.is_draw() => return Score::new(0),
             Some(_) => return Score::mated(ply),
             None => pos.evaluate().saturate(),
@@ -547,8 +1445,13 @@ mod tests {
 
         let moves: ArrayVec<_, 255> = pos
             .moves()
-            .filter(|m| ply < depth || !m.is_quiet())
             .flatten()
+            .filter(|m| {
+                ply < depth
+                    || pos.is_check()
+                    || !m.is_quiet()
+                    || (ply == depth && pos.gives_check(*m))
+            })
             .collect();
 
         if ply >= Ply::MAX || moves.is_empty() {
@@ -568,64 +1471,144 @@ mod tests {
         alpha
     }
 
+    #[proptest]
+    fn history_bonus_is_non_negative_and_increases_with_draft(
+        #[by_ref] e: Engine,
+        a: Depth,
+        #[filter(#a <= #b)] b: Depth,
+    ) {
+        let search = Search::new(&e.shared, Control::Unlimited);
+        assert!(search.bonus(a) >= 0);
+        assert!(search.bonus(a) <= search.bonus(b));
+    }
+
     #[proptest]
     fn hash_is_an_upper_limit_for_table_size(o: Options) {
         let e = Engine::with_options(&o);
-        prop_assume!(e.tt.capacity() > 1);
-        assert!(e.tt.size() <= o.hash);
+        prop_assume!(e.shared.tt.capacity() > 1);
+        assert!(e.shared.tt.size() <= o.hash);
+    }
+
+    #[proptest]
+    fn set_hash_preserves_size_limit(mut e: Engine, h: HashSize) {
+        e.set_hash(h);
+        prop_assume!(e.shared.tt.capacity() > 1);
+        assert!(e.shared.tt.size() <= h);
+    }
+
+    #[proptest]
+    fn set_threads_changes_thread_count(mut e: Engine, t: ThreadCount) {
+        e.set_threads(t);
+        assert_eq!(e.threads, t);
+    }
+
+    #[proptest]
+    fn set_threads_retires_surplus_workers(mut e: Engine, pos: Evaluator, t: ThreadCount) {
+        e.search(&pos, &Limits::Depth(Depth::new(1)));
+        e.set_threads(t);
+        assert!(e.pool.len() <= t.get());
+    }
+
+    #[proptest]
+    fn search_keeps_workers_parked_between_searches(e: Engine, pos: Evaluator, d: Depth) {
+        e.search(&pos, &Limits::Depth(d));
+        assert_eq!(e.pool.len(), e.threads.get());
+        e.search(&pos, &Limits::Depth(d));
+        assert_eq!(e.pool.len(), e.threads.get());
+    }
+
+    #[proptest]
+    fn time_to_search_reserves_move_overhead(
+        e: Engine,
+        pos: Evaluator,
+        #[strategy(..u32::MAX as u64)] c: u64,
+        #[strategy(..u32::MAX as u64)] i: u64,
+        m: Option<u32>,
+    ) {
+        let (c, i) = (Duration::from_millis(c), Duration::from_millis(i));
+        let time = e.time_to_search(&pos, &Limits::Clock(c, i, m));
+        assert!(time.start <= time.end);
+        assert!(time.end <= c.saturating_sub(e.overhead));
+    }
+
+    #[proptest]
+    fn time_to_search_spends_more_time_if_fewer_moves_to_go(
+        e: Engine,
+        pos: Evaluator,
+        #[strategy(..u32::MAX as u64)] c: u64,
+        #[strategy(1u32..40)] m: u32,
+    ) {
+        let c = Duration::from_millis(c);
+        let more = e.time_to_search(&pos, &Limits::Clock(c, Duration::ZERO, Some(m + 1)));
+        let fewer = e.time_to_search(&pos, &Limits::Clock(c, Duration::ZERO, Some(m)));
+        assert!(more.start <= fewer.start);
+        assert!(more.end <= fewer.end);
+    }
+
+    #[proptest]
+    fn clear_discards_transpositions(
+        #[by_ref]
+        #[filter(#e.shared.tt.capacity() > 0)]
+        mut e: Engine,
+        pos: Evaluator,
+        t: Transposition,
+    ) {
+        e.shared.tt.set(pos.zobrist(), t);
+        e.clear();
+        assert_eq!(e.shared.tt.get(pos.zobrist()), None);
     }
 
     #[proptest]
     fn nw_returns_transposition_if_beta_too_low(
         #[by_ref]
-        #[filter(#e.tt.capacity() > 0)]
+        #[filter(#e.shared.tt.capacity() > 0)]
         e: Engine,
-        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[filter(#pos.outcome().is_none() && #pos.repetition().is_none())] pos: Evaluator,
         #[filter((Value::lower()..Value::upper()).contains(&#b))] b: Score,
-        d: Depth,
+        #[filter(#d > 0)] d: Depth,
         #[filter(#p >= 0)] p: Ply,
         #[filter(#s.mate().is_none() && #s >= #b)] s: Score,
         #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
     ) {
         let tpos = Transposition::new(ScoreBound::Lower(s), d, m);
-        e.tt.set(pos.zobrist(), tpos);
-        let mut search = Search::new(&e, Control::Unlimited);
+        e.shared.tt.set(pos.zobrist(), tpos);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
         assert_eq!(search.nw::<1>(&pos, b, d, p), Ok(Pv::empty(s)));
     }
 
     #[proptest]
     fn nw_returns_transposition_if_beta_too_high(
         #[by_ref]
-        #[filter(#e.tt.capacity() > 0)]
+        #[filter(#e.shared.tt.capacity() > 0)]
         e: Engine,
-        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[filter(#pos.outcome().is_none() && #pos.repetition().is_none())] pos: Evaluator,
         #[filter((Value::lower()..Value::upper()).contains(&#b))] b: Score,
-        d: Depth,
+        #[filter(#d > 0)] d: Depth,
         #[filter(#p >= 0)] p: Ply,
         #[filter(#s.mate().is_none() && #s < #b)] s: Score,
         #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
     ) {
         let tpos = Transposition::new(ScoreBound::Upper(s), d, m);
-        e.tt.set(pos.zobrist(), tpos);
-        let mut search = Search::new(&e, Control::Unlimited);
+        e.shared.tt.set(pos.zobrist(), tpos);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
         assert_eq!(search.nw::<1>(&pos, b, d, p), Ok(Pv::empty(s)));
     }
 
     #[proptest]
     fn nw_returns_transposition_if_exact(
         #[by_ref]
-        #[filter(#e.tt.capacity() > 0)]
+        #[filter(#e.shared.tt.capacity() > 0)]
         e: Engine,
-        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[filter(#pos.outcome().is_none() && #pos.repetition().is_none())] pos: Evaluator,
         #[filter((Value::lower()..Value::upper()).contains(&#b))] b: Score,
-        d: Depth,
+        #[filter(#d > 0)] d: Depth,
         #[filter(#p >= 0)] p: Ply,
         #[filter(#s.mate().is_none())] s: Score,
         #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
     ) {
         let tpos = Transposition::new(ScoreBound::Exact(s), d, m);
-        e.tt.set(pos.zobrist(), tpos);
-        let mut search = Search::new(&e, Control::Unlimited);
+        e.shared.tt.set(pos.zobrist(), tpos);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
         assert_eq!(search.nw::<1>(&pos, b, d, p), Ok(Pv::empty(s)));
     }
 
@@ -637,7 +1620,7 @@ mod tests {
         d: Depth,
         #[filter(#p > 0)] p: Ply,
     ) {
-        let mut search = Search::new(&e, Control::Unlimited);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
 
         assert_eq!(
             search.nw::<1>(&pos, b, d, p)? < b,
@@ -645,6 +1628,114 @@ mod tests {
         );
     }
 
+    #[proptest]
+    fn nw_finds_score_bound_if_move_excluded(
+        #[by_ref] e: Engine,
+        #[filter(#pos.outcome().is_none() && #pos.repetition().is_none())] pos: Evaluator,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+        #[filter((Value::lower()..Value::upper()).contains(&#b))] b: Score,
+        d: Depth,
+        #[filter(#p > 0 && #d > #p)] p: Ply,
+    ) {
+        let mut search = Search::new(&e.shared, Control::Unlimited);
+        search.excluded[p.cast::<usize>()] = Some(m);
+
+        let score = pos.moves().flatten().filter(|&n| n != m).map(|n| {
+            let mut next = pos.clone();
+            next.play(n);
+            -alphabeta(&next, -b..-b + 1, d, p + 1)
+        });
+
+        assert_eq!(
+            search.nw::<1>(&pos, b, d, p)? < b,
+            score.max().is_none_or(|s| s < b)
+        );
+    }
+
+    #[proptest]
+    fn nw_never_returns_excluded_move(
+        #[by_ref] e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+        #[filter((Value::lower()..Value::upper()).contains(&#b))] b: Score,
+        d: Depth,
+        #[filter(#p >= 0)] p: Ply,
+    ) {
+        let mut search = Search::new(&e.shared, Control::Unlimited);
+        search.excluded[p.cast::<usize>()] = Some(m);
+        assert_ne!(search.nw::<1>(&pos, b, d, p)?.head(), Some(m));
+    }
+
+    #[proptest]
+    fn nw_does_not_overwrite_transposition_if_move_excluded(
+        #[by_ref]
+        #[filter(#e.shared.tt.capacity() > 0)]
+        e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[map(|s: Selector| s.select(#pos.moves().flatten()))] m: Move,
+        #[filter((Value::lower()..Value::upper()).contains(&#b))] b: Score,
+        d: Depth,
+        #[filter(#p >= 0)] p: Ply,
+        t: Transposition,
+    ) {
+        e.shared.tt.set(pos.zobrist(), t);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
+        search.excluded[p.cast::<usize>()] = Some(m);
+        search.nw::<1>(&pos, b, d, p)?;
+
+        // Transpositions of other positions may still replace it.
+        let tpos = e.shared.tt.get(pos.zobrist());
+        assert!(tpos.is_none_or(|tpos| tpos == t));
+    }
+
+    #[proptest]
+    fn nw_ignores_upcoming_repetition_of_positions_before_root(
+        #[by_ref] e: Engine,
+        #[strategy(1i8..=3)] p: i8,
+        #[filter(#d > #p)] d: Depth,
+    ) {
+        let mut pos: Evaluator =
+            "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse()?;
+        for m in ["g1f3", "g8f6", "f3g1"] {
+            pos.play(pos.moves().flatten().find(|n| n.to_string() == m).unwrap());
+        }
+
+        let nodes = Counter::new(1);
+        let timer = Timer::infinite();
+        let trigger = Trigger::armed();
+        let ctrl = Control::Limited(&nodes, &timer, &trigger);
+        let mut search = Search::new(&e.shared, ctrl);
+        let beta = Score::new(0);
+
+        assert_eq!(search.nw::<1>(&pos, beta, d, Ply::new(p)), Err(Interrupted));
+    }
+
+    #[proptest]
+    fn pvs_draws_if_side_to_move_can_force_repetition_within_search_tree(
+        #[by_ref] e: Engine,
+        #[strategy(4i8..=7)] p: i8,
+        d: Depth,
+    ) {
+        let fen = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let mut pos: Evaluator = fen.parse()?;
+        for m in ["g1f3", "g8f6", "f3g1"] {
+            pos.play(pos.moves().flatten().find(|n| n.to_string() == m).unwrap());
+        }
+
+        // The same position, but without the history that allows black to force a repetition.
+        let fresh: Evaluator = pos.to_string().parse()?;
+
+        // The main search is never this deep in test builds, so the node is searched directly.
+        let beta = Score::new(0);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
+        let pv = search.pvs::<1>(&pos, beta - 1..beta, d, Ply::new(p))?;
+        assert_eq!(pv.score(), Score::new(0));
+
+        let mut search = Search::new(&e.shared, Control::Unlimited);
+        let pv = search.pvs::<1>(&fresh, beta - 1..beta, d, Ply::new(p))?;
+        assert!(pv.score() < 0);
+    }
+
     #[proptest]
     fn ab_aborts_if_maximum_number_of_nodes_visited(
         e: Engine,
@@ -657,7 +1748,7 @@ mod tests {
         let timer = Timer::infinite();
         let trigger = Trigger::armed();
         let ctrl = Control::Limited(&nodes, &timer, &trigger);
-        let mut search = Search::new(&e, ctrl);
+        let mut search = Search::new(&e.shared, ctrl);
         assert_eq!(search.ab::<1>(&pos, b, d, p), Err(Interrupted));
     }
 
@@ -673,7 +1764,7 @@ mod tests {
         let timer = Timer::new(Duration::ZERO);
         let trigger = Trigger::armed();
         let ctrl = Control::Limited(&nodes, &timer, &trigger);
-        let mut search = Search::new(&e, ctrl);
+        let mut search = Search::new(&e.shared, ctrl);
         std::thread::sleep(Duration::from_millis(1));
         assert_eq!(search.ab::<1>(&pos, b, d, p), Err(Interrupted));
     }
@@ -690,18 +1781,18 @@ mod tests {
         let timer = Timer::infinite();
         let trigger = Trigger::disarmed();
         let ctrl = Control::Limited(&nodes, &timer, &trigger);
-        let mut search = Search::new(&e, ctrl);
+        let mut search = Search::new(&e.shared, ctrl);
         assert_eq!(search.ab::<1>(&pos, b, d, p), Err(Interrupted));
     }
 
     #[proptest]
     fn ab_returns_static_evaluation_if_max_ply(
         e: Engine,
-        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[filter(#pos.outcome().is_none() && #pos.repetition().is_none())] pos: Evaluator,
         #[filter(!#b.is_empty())] b: Range<Score>,
         d: Depth,
     ) {
-        let mut search = Search::new(&e, Control::Unlimited);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
 
         assert_eq!(
             search.ab::<1>(&pos, b, d, Ply::upper()),
@@ -717,7 +1808,7 @@ mod tests {
         d: Depth,
         #[filter(#p > 0 || #pos.outcome() != Some(Outcome::DrawByThreefoldRepetition))] p: Ply,
     ) {
-        let mut search = Search::new(&e, Control::Unlimited);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
 
         assert_eq!(search.ab::<1>(&pos, b, d, p), Ok(Pv::empty(Score::new(0))));
     }
@@ -730,7 +1821,7 @@ mod tests {
         d: Depth,
         #[filter(#p > 0)] p: Ply,
     ) {
-        let mut search = Search::new(&e, Control::Unlimited);
+        let mut search = Search::new(&e.shared, Control::Unlimited);
 
         assert_eq!(
             search.ab::<1>(&pos, b, d, p),
@@ -745,11 +1836,11 @@ mod tests {
         let timer = Timer::new(time.end);
         let trigger = Trigger::armed();
         let ctrl = Control::Limited(&nodes, &timer, &trigger);
-        let mut search = Search::new(&e, ctrl);
+        let mut search = Search::new(&e.shared, ctrl);
 
         assert_eq!(
-            e.search(&pos, &Limits::Depth(d), &Trigger::armed()).score(),
-            search.aw::<0>(&pos, d, time).score()
+            e.search(&pos, &Limits::Depth(d)).score(),
+            search.aw::<0>(&pos, d, time, |_, _| {}).score()
         );
     }
 
@@ -758,19 +1849,90 @@ mod tests {
         let limits = Limits::Depth(d);
 
         assert_eq!(
-            e.search(&pos, &limits, &Trigger::armed()).score(),
-            e.search(&pos, &limits, &Trigger::armed()).score()
+            e.search(&pos, &limits).score(),
+            e.search(&pos, &limits).score()
         );
     }
 
+    #[cfg(feature = "stats")]
+    #[proptest]
+    fn search_collects_stats_from_every_thread(
+        e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        d: Depth,
+    ) {
+        e.search(&pos, &Limits::Depth(d));
+        let stats = e.stats();
+        assert!(stats.get(Stat::Node) >= e.threads.cast());
+        assert!(stats.get(Stat::FirstMoveCutoff) <= stats.get(Stat::Cutoff));
+        assert!(stats.get(Stat::TtHit) <= stats.get(Stat::Node) + stats.get(Stat::QsNode));
+    }
+
+    #[cfg(feature = "trace")]
+    #[proptest]
+    fn search_records_tree_into_trace(
+        mut e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        d: Depth,
+        #[filter(#p >= 0)] p: Ply,
+        #[strategy(..64u64)] n: u64,
+    ) {
+        let path = std::env::temp_dir().join(format!("cinder-engine-{}.trace", std::process::id()));
+        e.set_trace(Some(Trace::create(&path, p, n)?));
+        e.search(&pos, &Limits::Depth(d));
+
+        let file = std::io::BufReader::new(std::fs::File::open(&path)?);
+        let nodes = TraceReader::new(file).collect::<Result<Vec<_>, _>>()?;
+        assert!(nodes.len() as u64 <= n * e.threads.cast::<u64>());
+
+        for node in nodes {
+            assert!(node.thread < e.threads.get());
+            assert!((0..=p.get()).contains(&node.ply.get()));
+            assert_eq!(node.path.len(), node.ply.cast::<usize>());
+            assert!(node.alpha < node.beta);
+        }
+    }
+
+    #[proptest]
+    fn deterministic_search_is_reproducible(
+        #[filter(#o.threads.get() > 1)] o: Options,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[strategy(..5000u64)] n: u64,
+    ) {
+        let o = Options {
+            deterministic: true,
+            ..o
+        };
+
+        let pv = Engine::with_options(&o).search(&pos, &Limits::Nodes(n));
+        assert_eq!(Engine::with_options(&o).search(&pos, &Limits::Nodes(n)), pv);
+    }
+
+    #[proptest]
+    fn deterministic_search_is_reproducible_across_searches(
+        #[filter(#o.threads.get() > 1)] o: Options,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        #[strategy(..5000u64)] n: u64,
+    ) {
+        let o = Options {
+            deterministic: true,
+            ..o
+        };
+
+        let (a, b) = (Engine::with_options(&o), Engine::with_options(&o));
+        for _ in 0..3 {
+            let pv = a.search(&pos, &Limits::Nodes(n));
+            assert_eq!(b.search(&pos, &Limits::Nodes(n)), pv);
+        }
+    }
+
     #[proptest]
     fn search_extends_time_to_find_some_pv(
         e: Engine,
         #[filter(#pos.outcome().is_none())] pos: Evaluator,
     ) {
         let limits = Duration::ZERO.into();
-        let trigger = Trigger::armed();
-        assert_ne!(e.search(&pos, &limits, &trigger).head(), None);
+        assert_ne!(e.search(&pos, &limits).head(), None);
     }
 
     #[proptest]
@@ -779,8 +1941,7 @@ mod tests {
         #[filter(#pos.outcome().is_none())] pos: Evaluator,
     ) {
         let limits = Depth::lower().into();
-        let trigger = Trigger::armed();
-        assert_ne!(e.search(&pos, &limits, &trigger).head(), None);
+        assert_ne!(e.search(&pos, &limits).head(), None);
     }
 
     #[proptest]
@@ -789,7 +1950,163 @@ mod tests {
         #[filter(#pos.outcome().is_none())] pos: Evaluator,
     ) {
         let limits = Limits::None;
-        let trigger = Trigger::armed();
-        assert_ne!(e.search(&pos, &limits, &trigger).head(), None);
+        assert_ne!(e.search(&pos, &limits).head(), None);
+    }
+
+    #[proptest]
+    fn search_chooses_legal_move(
+        e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        d: Depth,
+    ) {
+        let pv = e.search(&pos, &Limits::Depth(d));
+        let m = pv.head().assume();
+        assert!(pos.moves().flatten().any(|n| n == m));
+    }
+
+    #[test]
+    fn vote_chooses_nothing_without_ballots() {
+        assert_eq!(vote(&[]), None);
+    }
+
+    #[proptest]
+    fn vote_chooses_one_of_the_ballots(
+        #[filter(#ballots.iter().any(|(d, _)| *d > 0))]
+        #[strategy(vec(any::<(Depth, Pv)>(), 1..8))]
+        ballots: Vec<(Depth, Pv)>,
+    ) {
+        assert!(ballots.contains(vote(&ballots).assume()));
+    }
+
+    #[proptest]
+    fn vote_chooses_move_found_by_every_thread(
+        m: Move,
+        #[strategy(vec(any::<(Depth, Pv)>(), 1..8))]
+        #[map(|bs: Vec<(Depth, Pv)>| bs.into_iter().map(|(d, pv)| (d, #m >> pv)).collect())]
+        ballots: Vec<(Depth, Pv)>,
+    ) {
+        prop_assume!(ballots.iter().any(|(d, _)| *d > 0));
+        assert_eq!(vote(&ballots).assume().1.head(), Some(m));
+    }
+
+    #[proptest]
+    fn vote_disregards_threads_that_completed_no_iteration(
+        #[strategy(vec(any::<(Depth, Pv)>(), ..8))] ballots: Vec<(Depth, Pv)>,
+        pv: Pv,
+    ) {
+        let mut all = ballots.clone();
+        all.push((Depth::new(0), pv));
+        assert_eq!(vote(&all), vote(&ballots));
+    }
+
+    #[proptest]
+    fn vote_chooses_legal_move_from_completed_iteration(
+        e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        d: Depth,
+    ) {
+        // Every iteration completed by the search stands for a thread that stopped there.
+        let mut handle = e.start(&pos, &Limits::Depth(d));
+        let progress: Vec<_> = block_on(handle.subscribe().collect());
+        let mut ballots = Vec::from_iter(progress.iter().map(|p| (p.depth(), p.pv().clone())));
+        ballots.push((Depth::new(0), Pv::empty(Score::new(0))));
+        handle.wait();
+
+        let (d, pv) = vote(&ballots).unwrap();
+        assert!(*d > 0);
+        assert!(pos.moves().flatten().any(|m| Some(m) == pv.head()));
+    }
+
+    #[proptest]
+    fn vote_prefers_deeper_iteration_if_scores_are_equal(
+        s: Score,
+        #[filter(#a.head() != #b.head())] a: Line<3>,
+        b: Line<3>,
+        #[filter(#d > #e)] d: Depth,
+        e: Depth,
+    ) {
+        let ballots = [(e, Pv::new(s, a)), (d, Pv::new(s, b.clone()))];
+        assert_eq!(vote(&ballots), Some(&(d, Pv::new(s, b))));
+    }
+
+    #[proptest]
+    fn start_finds_the_same_score_as_search(e: Engine, pos: Evaluator, d: Depth) {
+        let limits = Limits::Depth(d);
+
+        assert_eq!(
+            e.start(&pos, &limits).wait().score(),
+            e.search(&pos, &limits).score()
+        );
+    }
+
+    #[proptest]
+    fn start_reports_progress_of_every_iteration(
+        e: Engine,
+        #[filter(#pos.outcome().is_none())] pos: Evaluator,
+        d: Depth,
+    ) {
+        let mut handle = e.start(&pos, &Limits::Depth(d));
+        let progress: Vec<_> = block_on(handle.subscribe().collect());
+        let pv = handle.wait();
+
+        let depths = Vec::from_iter(progress.iter().map(Progress::depth));
+        let expected = Vec::from_iter((1..=d.get().max(1)).map(Depth::new));
+        assert_eq!(depths, expected);
+        assert_eq!(progress.last().map(Progress::pv), Some(&pv));
+    }
+
+    #[proptest]
+    fn start_can_only_be_subscribed_to_once(e: Engine, pos: Evaluator, d: Depth) {
+        let mut handle = e.start(&pos, &Limits::Depth(d));
+        let _ = handle.subscribe();
+        assert_eq!(block_on(handle.subscribe().collect::<Vec<_>>()), []);
+    }
+
+    #[proptest]
+    fn start_returns_pv_only_once(e: Engine, pos: Evaluator, d: Depth) {
+        let mut handle = e.start(&pos, &Limits::Depth(d));
+        block_on(&mut handle);
+        assert_eq!(handle.try_pv(), None);
+    }
+
+    #[proptest]
+    fn stopped_search_finds_some_pv(e: Engine, #[filter(#pos.outcome().is_none())] pos: Evaluator) {
+        let handle = e.start(&pos, &Limits::None);
+        handle.stop();
+        assert_ne!(handle.wait().head(), None);
+    }
+
+    #[proptest]
+    fn dropping_handle_stops_search(e: Engine, pos: Evaluator) {
+        drop(e.start(&pos, &Limits::None));
+    }
+
+    #[proptest]
+    fn set_hash_during_search_is_safe(mut e: Engine, pos: Evaluator, h: HashSize) {
+        let handle = e.start(&pos, &Limits::None);
+        e.set_hash(h);
+        e.clear();
+        handle.stop();
+        handle.wait();
+        prop_assume!(e.shared.tt.capacity() > 1);
+        assert!(e.shared.tt.size() <= h);
+    }
+
+    #[proptest]
+    fn set_hash_stops_search_and_keeps_table(mut e: Engine, pos: Evaluator, h: HashSize) {
+        let tt = Arc::as_ptr(&e.shared.tt);
+        let handle = e.start(&pos, &Limits::None);
+        e.set_hash(h);
+        assert_eq!(Arc::as_ptr(&e.shared.tt), tt);
+        handle.wait();
+    }
+
+    #[proptest]
+    fn clear_stops_search_and_keeps_table(mut e: Engine, pos: Evaluator) {
+        let tt = Arc::as_ptr(&e.shared.tt);
+        let handle = e.start(&pos, &Limits::None);
+        e.clear();
+        assert_eq!(Arc::as_ptr(&e.shared.tt), tt);
+        handle.wait();
     }
 }
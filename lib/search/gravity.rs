@@ -1,7 +1,6 @@
 use crate::chess::{Move, Position};
 use crate::util::Assume;
-use derive_more::Debug;
-use std::sync::atomic::{AtomicI8, Ordering::Relaxed};
+use std::sync::atomic::{AtomicI16, Ordering::Relaxed};
 
 #[cfg(test)]
 use proptest::prelude::*;
@@ -15,16 +14,26 @@ pub trait Gravity {
 
     /// Update the [`Self::Bonus`] for a [`Move`].
     fn update(&self, pos: &Position, m: Move, bonus: Self::Bonus);
+
+    /// Decays the accumulated history of every [`Move`].
+    fn age(&self);
 }
 
-/// The unit of [`Gravity`].
+/// The unit of [`Gravity`], whose magnitude never exceeds `MAX`.
 #[derive(Debug, Default)]
 #[cfg_attr(test, derive(test_strategy::Arbitrary))]
 #[repr(transparent)]
-pub struct Graviton(#[cfg_attr(test, strategy(any::<i8>().prop_map_into()))] AtomicI8);
+pub struct Graviton<const MAX: i16 = 16384>(
+    #[cfg_attr(test, strategy((-MAX..=MAX).prop_map_into()))] AtomicI16,
+);
+
+impl<const MAX: i16> Graviton<MAX> {
+    /// The maximum magnitude of the accumulated history.
+    pub const MAX: i16 = MAX;
+}
 
-impl Gravity for Graviton {
-    type Bonus = i8;
+impl<const MAX: i16> Gravity for Graviton<MAX> {
+    type Bonus = i16;
 
     #[inline(always)]
     fn get(&self, _: &Position, _: Move) -> Self::Bonus {
@@ -33,13 +42,18 @@ impl Gravity for Graviton {
 
     #[inline(always)]
     fn update(&self, _: &Position, _: Move, bonus: Self::Bonus) {
-        let bonus = bonus.max(-i8::MAX);
+        let bonus = bonus.clamp(-MAX, MAX) as i32;
         let result = self.0.fetch_update(Relaxed, Relaxed, |h| {
-            Some((bonus as i16 - bonus.abs() as i16 * h as i16 / 127 + h as i16) as i8)
+            Some((bonus - bonus.abs() * h as i32 / MAX as i32 + h as i32) as i16)
         });
 
         result.assume();
     }
+
+    #[inline(always)]
+    fn age(&self) {
+        self.0.store(self.0.load(Relaxed) / 2, Relaxed);
+    }
 }
 
 impl<T: Gravity> Gravity for &T {
@@ -54,6 +68,11 @@ impl<T: Gravity> Gravity for &T {
     fn update(&self, pos: &Position, m: Move, bonus: Self::Bonus) {
         (*self).update(pos, m, bonus)
     }
+
+    #[inline(always)]
+    fn age(&self) {
+        (*self).age()
+    }
 }
 
 impl<T: Gravity<Bonus: Default>> Gravity for Option<T> {
@@ -71,4 +90,42 @@ impl<T: Gravity<Bonus: Default>> Gravity for Option<T> {
             g.update(pos, m, bonus);
         }
     }
+
+    #[inline(always)]
+    fn age(&self) {
+        if let Some(g) = self {
+            g.age();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn update_never_exceeds_maximum(g: Graviton, pos: Position, m: Move, b: i16) {
+        g.update(&pos, m, b);
+        assert!(g.get(&pos, m).abs() <= <Graviton>::MAX);
+    }
+
+    #[proptest]
+    fn update_moves_history_towards_bonus(g: Graviton, pos: Position, m: Move, b: i16) {
+        let h = g.get(&pos, m);
+        g.update(&pos, m, b);
+
+        if b >= 0 {
+            assert!(g.get(&pos, m) >= h);
+        } else {
+            assert!(g.get(&pos, m) <= h);
+        }
+    }
+
+    #[proptest]
+    fn age_halves_history(g: Graviton, pos: Position, m: Move) {
+        let h = g.get(&pos, m);
+        g.age();
+        assert_eq!(g.get(&pos, m), h / 2);
+    }
 }
@@ -0,0 +1,120 @@
+use crate::search::{Depth, Pv};
+use crate::util::Trigger;
+use derive_more::Constructor;
+use futures::channel::{mpsc, oneshot};
+use futures::future::{Fuse, FusedFuture};
+use futures::{executor::block_on, prelude::*, stream::FusedStream};
+use std::task::{Context, Poll};
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+/// A snapshot of the search reported after every completed iteration.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Constructor)]
+pub struct Progress {
+    depth: Depth,
+    time: Duration,
+    nodes: u64,
+    pv: Pv,
+}
+
+impl Progress {
+    /// The depth of the iteration just completed.
+    #[inline(always)]
+    pub fn depth(&self) -> Depth {
+        self.depth
+    }
+
+    /// The time elapsed since the search started.
+    #[inline(always)]
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// The number of nodes searched so far.
+    #[inline(always)]
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// The best [`Pv`] found so far.
+    #[inline(always)]
+    pub fn pv(&self) -> &Pv {
+        &self.pv
+    }
+}
+
+/// A handle to a search running in the background.
+///
+/// The handle is a [`Future`] that resolves to the result of the search, by default the final [`Pv`].
+/// Dropping it stops the search and waits for it to finish.
+#[derive(Debug)]
+pub struct SearchHandle<T = Pv> {
+    stopper: Arc<Trigger>,
+    pv: Fuse<oneshot::Receiver<T>>,
+    progress: Option<mpsc::UnboundedReceiver<Progress>>,
+}
+
+impl<T> SearchHandle<T> {
+    pub(super) fn new(
+        stopper: Arc<Trigger>,
+        pv: oneshot::Receiver<T>,
+        progress: mpsc::UnboundedReceiver<Progress>,
+    ) -> Self {
+        SearchHandle {
+            stopper,
+            pv: pv.fuse(),
+            progress: Some(progress),
+        }
+    }
+
+    /// Signals the search to stop as soon as possible.
+    #[inline(always)]
+    pub fn stop(&self) {
+        self.stopper.disarm();
+    }
+
+    /// Returns the result of the search if it has finished.
+    ///
+    /// The result is only ever returned once.
+    pub fn try_pv(&mut self) -> Option<T> {
+        self.now_or_never()
+    }
+
+    /// Blocks until the search finishes and returns its result.
+    pub fn wait(mut self) -> T {
+        block_on(&mut self)
+    }
+
+    /// Subscribes to the [`Progress`] of the search.
+    ///
+    /// Only the first subscriber receives any events.
+    pub fn subscribe(&mut self) -> impl FusedStream<Item = Progress> + Unpin + use<T> {
+        self.progress.take().unwrap_or_else(|| mpsc::unbounded().1)
+    }
+}
+
+impl<T> Future for SearchHandle<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.pv.poll_unpin(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(pv)) => Poll::Ready(pv),
+            Poll::Ready(Err(_)) => panic!("the search thread panicked"),
+        }
+    }
+}
+
+impl<T> FusedFuture for SearchHandle<T> {
+    fn is_terminated(&self) -> bool {
+        self.pv.is_terminated()
+    }
+}
+
+impl<T> Drop for SearchHandle<T> {
+    fn drop(&mut self) {
+        self.stop();
+        if !self.pv.is_terminated() {
+            block_on(&mut self.pv).ok();
+        }
+    }
+}
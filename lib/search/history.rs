@@ -30,4 +30,11 @@ impl Gravity for History {
         let (wc, wt) = (m.whence() as usize, m.whither() as usize);
         self.0[pos.turn() as usize][m.is_capture() as usize][wc][wt].update(pos, m, bonus);
     }
+
+    #[inline(always)]
+    fn age(&self) {
+        for g in self.0.iter().flatten().flatten().flatten() {
+            g.age();
+        }
+    }
 }
@@ -18,9 +18,9 @@ pub enum Limits {
     /// The maximum amount of time to spend searching.
     Time(Duration),
 
-    /// The time remaining on the clock.
+    /// The time remaining on the clock, the increment, and the number of moves to go.
     #[from(ignore)]
-    Clock(Duration, Duration),
+    Clock(Duration, Duration, Option<u32>),
 }
 
 impl Limits {
@@ -47,7 +47,7 @@ impl Limits {
     pub fn time(&self) -> Duration {
         match self {
             Limits::Time(t) => *t,
-            Limits::Clock(t, _) => *t,
+            Limits::Clock(t, _, _) => *t,
             _ => Duration::MAX,
         }
     }
@@ -56,7 +56,7 @@ impl Limits {
     #[inline(always)]
     pub fn clock(&self) -> Duration {
         match self {
-            Limits::Clock(t, _) => *t,
+            Limits::Clock(t, _, _) => *t,
             _ => Duration::MAX,
         }
     }
@@ -65,10 +65,19 @@ impl Limits {
     #[inline(always)]
     pub fn increment(&self) -> Duration {
         match self {
-            Limits::Clock(_, i) => *i,
+            Limits::Clock(_, i, _) => *i,
             _ => Duration::ZERO,
         }
     }
+
+    /// Moves to go until the next time control, if known.
+    #[inline(always)]
+    pub fn movestogo(&self) -> Option<u32> {
+        match self {
+            Limits::Clock(_, _, m) => *m,
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -82,11 +91,11 @@ mod tests {
     }
 
     #[proptest]
-    fn depth_returns_max_by_default(n: u64, t: Duration, i: Duration) {
+    fn depth_returns_max_by_default(n: u64, t: Duration, i: Duration, m: Option<u32>) {
         assert_eq!(Limits::None.depth(), Depth::MAX);
         assert_eq!(Limits::Nodes(n).depth(), Depth::MAX);
         assert_eq!(Limits::Time(t).depth(), Depth::MAX);
-        assert_eq!(Limits::Clock(t, i).depth(), Depth::MAX);
+        assert_eq!(Limits::Clock(t, i, m).depth(), Depth::MAX);
     }
 
     #[proptest]
@@ -95,11 +104,11 @@ mod tests {
     }
 
     #[proptest]
-    fn nodes_returns_max_by_default(d: Depth, t: Duration, i: Duration) {
+    fn nodes_returns_max_by_default(d: Depth, t: Duration, i: Duration, m: Option<u32>) {
         assert_eq!(Limits::None.nodes(), u64::MAX);
         assert_eq!(Limits::Depth(d).nodes(), u64::MAX);
         assert_eq!(Limits::Time(t).nodes(), u64::MAX);
-        assert_eq!(Limits::Clock(t, i).nodes(), u64::MAX);
+        assert_eq!(Limits::Clock(t, i, m).nodes(), u64::MAX);
     }
 
     #[proptest]
@@ -108,16 +117,22 @@ mod tests {
     }
 
     #[proptest]
-    fn time_returns_max_or_clock_by_default(d: Depth, n: u64, t: Duration, i: Duration) {
+    fn time_returns_max_or_clock_by_default(
+        d: Depth,
+        n: u64,
+        t: Duration,
+        i: Duration,
+        m: Option<u32>,
+    ) {
         assert_eq!(Limits::None.time(), Duration::MAX);
         assert_eq!(Limits::Depth(d).time(), Duration::MAX);
         assert_eq!(Limits::Nodes(n).time(), Duration::MAX);
-        assert_eq!(Limits::Clock(t, i).time(), t);
+        assert_eq!(Limits::Clock(t, i, m).time(), t);
     }
 
     #[proptest]
-    fn clock_returns_value_if_set(t: Duration, i: Duration) {
-        assert_eq!(Limits::Clock(t, i).clock(), t);
+    fn clock_returns_value_if_set(t: Duration, i: Duration, m: Option<u32>) {
+        assert_eq!(Limits::Clock(t, i, m).clock(), t);
     }
 
     #[proptest]
@@ -129,8 +144,8 @@ mod tests {
     }
 
     #[proptest]
-    fn increment_returns_value_if_set(t: Duration, i: Duration) {
-        assert_eq!(Limits::Clock(t, i).increment(), i);
+    fn increment_returns_value_if_set(t: Duration, i: Duration, m: Option<u32>) {
+        assert_eq!(Limits::Clock(t, i, m).increment(), i);
     }
 
     #[proptest]
@@ -140,4 +155,17 @@ mod tests {
         assert_eq!(Limits::Nodes(n).increment(), Duration::ZERO);
         assert_eq!(Limits::Time(t).increment(), Duration::ZERO);
     }
+
+    #[proptest]
+    fn movestogo_returns_value_if_set(t: Duration, i: Duration, m: Option<u32>) {
+        assert_eq!(Limits::Clock(t, i, m).movestogo(), m);
+    }
+
+    #[proptest]
+    fn movestogo_returns_none_by_default(d: Depth, n: u64, t: Duration) {
+        assert_eq!(Limits::None.movestogo(), None);
+        assert_eq!(Limits::Depth(d).movestogo(), None);
+        assert_eq!(Limits::Nodes(n).movestogo(), None);
+        assert_eq!(Limits::Time(t).movestogo(), None);
+    }
 }
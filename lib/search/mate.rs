@@ -0,0 +1,461 @@
+use crate::chess::{Move, Position, Zobrist};
+use crate::search::{Control, HashSize, Interrupted};
+use crate::util::Integer;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// A proof or disproof number, where [`INFINITY`] stands for a solved node.
+type Number = u32;
+
+const INFINITY: Number = Number::MAX;
+
+/// The proof and disproof numbers of a node, from the point of view of the side to move.
+///
+/// The first number is the cost of proving the side to move wins,
+/// the second is the cost of proving it loses.
+type Numbers = (Number, Number);
+
+/// A forced mate, as the attacker's move along with every defense against it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MateSolution {
+    /// The attacker's move.
+    pub attack: Move,
+    /// Every legal reply of the defender, each followed by the rest of the mate.
+    ///
+    /// Empty if the attack is checkmate.
+    pub defenses: Vec<(Move, MateSolution)>,
+}
+
+impl MateSolution {
+    /// The number of moves until mate against the most stubborn defense.
+    pub fn moves(&self) -> usize {
+        let defenses = self.defenses.iter();
+        1 + defenses.map(|(_, s)| s.moves()).max().unwrap_or(0)
+    }
+
+    /// The moves played until mate against the most stubborn defense.
+    pub fn line(&self) -> Vec<Move> {
+        let mut line = vec![self.attack];
+        let defenses = self.defenses.iter();
+        if let Some((m, s)) = defenses.max_by_key(|(_, s)| s.moves()) {
+            line.push(*m);
+            line.extend(s.line());
+        }
+
+        line
+    }
+}
+
+impl Display for MateSolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.attack, f)?;
+
+        let mut defenses = self.defenses.iter();
+        if let Some((m, s)) = defenses.next() {
+            write!(f, " ({m} {s}")?;
+            for (m, s) in defenses {
+                write!(f, ", {m} {s}")?;
+            }
+
+            f.write_str(")")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A [depth-first proof-number search] for forced mates.
+///
+/// [depth-first proof-number search]: https://www.chessprogramming.org/Proof-Number_Search#Depth-First_Proof-Number_Search
+#[derive(Debug, Clone)]
+pub struct MateSolver {
+    table: HashMap<(Zobrist, u8), Numbers>,
+    capacity: usize,
+    nodes: u64,
+}
+
+impl Default for MateSolver {
+    fn default() -> Self {
+        Self::new(HashSize::default())
+    }
+}
+
+impl MateSolver {
+    /// Constructs a solver whose transposition store takes up roughly `size` bytes.
+    pub fn new(size: HashSize) -> Self {
+        // The overhead of the hash map is assumed to match the size of its entries.
+        let entry = size_of::<((Zobrist, u8), Numbers)>();
+        MateSolver {
+            table: HashMap::new(),
+            capacity: size.get() / (2 * entry),
+            nodes: 0,
+        }
+    }
+
+    /// The number of nodes visited by the last call to [`MateSolver::solve`].
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Forgets every node solved so far.
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    /// Proves or disproves that the side to move mates in at most `moves` moves.
+    ///
+    /// Returns the shortest mate found, [`None`] if there's none, or [`Interrupted`]
+    /// if either `ctrl` interrupts the search or the transposition store fills up.
+    /// Draws by repetition and by the fifty-move rule are disregarded.
+    pub fn solve(
+        &mut self,
+        pos: &Position,
+        moves: u32,
+        ctrl: Control<'_>,
+    ) -> Result<Option<MateSolution>, Interrupted> {
+        self.nodes = 0;
+
+        // The number of plies is capped, since the search is recursive.
+        for n in 1..=moves.min(u8::MAX as u32 / 2) {
+            let plies = (2 * n - 1) as u8;
+            if self.mid(pos, plies, (INFINITY, INFINITY), &ctrl)?.0 == 0 {
+                return Ok(Some(self.solution(pos, plies)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The numbers of a node that can be solved without searching, if any.
+    fn leaf(pos: &Position, plies: u8) -> Option<Numbers> {
+        // The attacker is to move at every odd ply.
+        let attacker = plies % 2 == 1;
+        if pos.moves().flatten().next().is_none() {
+            match attacker || pos.is_check() {
+                true => Some((INFINITY, 0)),
+                false => Some((0, INFINITY)),
+            }
+        } else if plies == 0 {
+            Some((0, INFINITY))
+        } else {
+            None
+        }
+    }
+
+    /// Stores the numbers of a node, unless the store is full.
+    ///
+    /// Nodes are never evicted, since a proof may depend on any of them.
+    fn store(&mut self, pos: &Position, plies: u8, numbers: Numbers) -> Result<(), Interrupted> {
+        let key = (pos.zobrist(), plies);
+        if self.table.len() >= self.capacity && !self.table.contains_key(&key) {
+            return Err(Interrupted);
+        }
+
+        self.table.insert(key, numbers);
+        Ok(())
+    }
+
+    /// The numbers of a node that is not being searched.
+    fn numbers(&self, pos: &Position, plies: u8, leaf: Option<Numbers>) -> Numbers {
+        leaf.or_else(|| self.table.get(&(pos.zobrist(), plies)).copied())
+            .unwrap_or((1, 1))
+    }
+
+    /// The multiple-iterative deepening step, which searches a node until
+    /// either of its numbers reaches the corresponding threshold.
+    fn mid(
+        &mut self,
+        pos: &Position,
+        plies: u8,
+        thresholds: Numbers,
+        ctrl: &Control<'_>,
+    ) -> Result<Numbers, Interrupted> {
+        ctrl.interrupted()?;
+        self.nodes += 1;
+
+        if let Some(numbers) = Self::leaf(pos, plies) {
+            self.store(pos, plies, numbers)?;
+            return Ok(numbers);
+        }
+
+        let children = Vec::from_iter(pos.moves().flatten().map(|m| {
+            let mut next = pos.clone();
+            next.play(m);
+            let leaf = Self::leaf(&next, plies - 1);
+            (next, leaf)
+        }));
+
+        loop {
+            let (mut proof, mut disproof) = (INFINITY, 0 as Number);
+            let (mut best, mut second) = (0, INFINITY);
+            for (i, (next, leaf)) in children.iter().enumerate() {
+                let (p, d) = self.numbers(next, plies - 1, *leaf);
+                disproof = disproof.saturating_add(p);
+                if d < proof {
+                    (best, second, proof) = (i, proof, d);
+                } else if d < second {
+                    second = d;
+                }
+            }
+
+            if proof >= thresholds.0 || disproof >= thresholds.1 {
+                self.store(pos, plies, (proof, disproof))?;
+                return Ok((proof, disproof));
+            }
+
+            let (next, leaf) = &children[best];
+            let (p, d) = self.numbers(next, plies - 1, *leaf);
+            let proof_threshold = match thresholds.1 {
+                INFINITY => INFINITY,
+                t => (t - disproof).saturating_add(p),
+            };
+
+            let disproof_threshold = thresholds.0.min(second.saturating_add(1));
+            debug_assert!(p < proof_threshold && d < disproof_threshold);
+            self.mid(next, plies - 1, (proof_threshold, disproof_threshold), ctrl)?;
+        }
+    }
+
+    /// Extracts the solution of a node proven to be a win for the attacker.
+    fn solution(&self, pos: &Position, plies: u8) -> MateSolution {
+        let mut moves = pos.moves().flatten();
+        let (attack, next) = moves
+            .find_map(|m| {
+                let mut next = pos.clone();
+                next.play(m);
+                let leaf = Self::leaf(&next, plies - 1);
+                let (_, disproof) = self.numbers(&next, plies - 1, leaf);
+                (disproof == 0).then_some((m, next))
+            })
+            .expect("a proven node must have a winning move");
+
+        let defenses = next.moves().flatten().map(|m| {
+            let mut pos = next.clone();
+            pos.play(m);
+            (m, self.solution(&pos, plies - 2))
+        });
+
+        MateSolution {
+            attack,
+            defenses: defenses.collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{Counter, Timer, Trigger};
+    use proptest::sample::Selector;
+    use std::{fmt::Debug, time::Duration};
+    use test_strategy::proptest;
+
+    /// Whether the side to move mates in at most `moves` moves, by brute force.
+    fn mates(pos: &Position, moves: u32) -> bool {
+        moves > 0
+            && pos.moves().flatten().any(|m| {
+                let mut next = pos.clone();
+                next.play(m);
+                next.is_checkmate()
+                    || (!next.is_stalemate()
+                        && next.moves().flatten().all(|r| {
+                            let mut pos = next.clone();
+                            pos.play(r);
+                            mates(&pos, moves - 1)
+                        }))
+            })
+    }
+
+    /// Asserts that `solution` is a complete proof of mate.
+    fn verify(pos: &Position, solution: &MateSolution) {
+        assert!(pos.moves().flatten().any(|m| m == solution.attack));
+
+        let mut next = pos.clone();
+        next.play(solution.attack);
+
+        let replies = Vec::from_iter(next.moves().flatten());
+        assert_eq!(solution.defenses.is_empty(), next.is_checkmate());
+        assert_eq!(solution.defenses.len(), replies.len());
+
+        for (m, s) in &solution.defenses {
+            assert!(replies.contains(m));
+            let mut pos = next.clone();
+            pos.play(*m);
+            verify(&pos, s);
+        }
+    }
+
+    #[proptest]
+    fn solve_agrees_with_brute_force(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=2u32)] n: u32,
+    ) {
+        let solution = MateSolver::default().solve(&pos, n, Control::Unlimited)?;
+        assert_eq!(solution.is_some(), mates(&pos, n));
+    }
+
+    #[proptest]
+    fn solve_finds_shortest_mate(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        if let Some(s) = MateSolver::default().solve(&pos, n, Control::Unlimited)? {
+            assert!(s.moves() <= n as usize);
+            assert!(!mates(&pos, s.moves() as u32 - 1));
+        }
+    }
+
+    #[proptest]
+    fn solution_is_complete(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        if let Some(s) = MateSolver::default().solve(&pos, n, Control::Unlimited)? {
+            verify(&pos, &s);
+        }
+    }
+
+    #[proptest]
+    fn solution_line_leads_to_checkmate(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        if let Some(s) = MateSolver::default().solve(&pos, n, Control::Unlimited)? {
+            let line = s.line();
+            assert_eq!(line.len(), 2 * s.moves() - 1);
+
+            let mut pos = pos;
+            for m in line {
+                pos.play(m);
+            }
+
+            assert!(pos.is_checkmate());
+        }
+    }
+
+    #[proptest]
+    fn solve_is_interrupted_once_node_limit_is_reached(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        let nodes = Counter::new(0);
+        let timer = Timer::infinite();
+        let trigger = Trigger::armed();
+        let ctrl = Control::Limited(&nodes, &timer, &trigger);
+        assert_eq!(MateSolver::default().solve(&pos, n, ctrl), Err(Interrupted));
+    }
+
+    #[proptest]
+    fn solve_is_interrupted_once_time_is_up(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        let nodes = Counter::new(u64::MAX);
+        let timer = Timer::new(Duration::ZERO);
+        let trigger = Trigger::armed();
+        let ctrl = Control::Limited(&nodes, &timer, &trigger);
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(MateSolver::default().solve(&pos, n, ctrl), Err(Interrupted));
+    }
+
+    #[proptest]
+    fn solve_is_interrupted_once_stopped(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        let nodes = Counter::new(u64::MAX);
+        let timer = Timer::infinite();
+        let trigger = Trigger::disarmed();
+        let ctrl = Control::Limited(&nodes, &timer, &trigger);
+        assert_eq!(MateSolver::default().solve(&pos, n, ctrl), Err(Interrupted));
+    }
+
+    #[proptest]
+    fn solve_is_interrupted_if_transposition_store_is_too_small(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        let mut solver = MateSolver::new(HashSize::new(0));
+        assert_eq!(solver.solve(&pos, n, Control::Unlimited), Err(Interrupted));
+    }
+
+    #[proptest]
+    fn transposition_store_never_exceeds_its_capacity(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+        #[strategy(0usize..4096)] size: usize,
+    ) {
+        let mut solver = MateSolver::new(HashSize::new(size));
+        solver.solve(&pos, n, Control::Unlimited).ok();
+        assert!(solver.table.len() <= solver.capacity);
+    }
+
+    #[proptest]
+    fn solve_reuses_transposition_store(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=3u32)] n: u32,
+    ) {
+        let mut solver = MateSolver::default();
+        let solution = solver.solve(&pos, n, Control::Unlimited)?;
+        let nodes = solver.nodes();
+        assert_eq!(solver.solve(&pos, n, Control::Unlimited)?, solution);
+        assert!(solver.nodes() <= nodes);
+    }
+
+    #[proptest]
+    fn solve_finds_no_mate_in_zero_moves(#[filter(#pos.outcome().is_none())] pos: Position) {
+        assert_eq!(
+            MateSolver::default().solve(&pos, 0, Control::Unlimited),
+            Ok(None)
+        );
+    }
+
+    #[proptest]
+    fn solution_is_printed_as_nested_defenses(
+        #[filter(#pos.outcome().is_none())] pos: Position,
+        #[strategy(1..=2u32)] n: u32,
+        sel: Selector,
+    ) {
+        if let Some(s) = MateSolver::default().solve(&pos, n, Control::Unlimited)? {
+            let printed = s.to_string();
+            assert!(printed.starts_with(&s.attack.to_string()));
+
+            if let Some((m, d)) = sel.try_select(&s.defenses) {
+                assert!(printed.contains(&format!("{m} {d}")));
+            }
+        }
+    }
+
+    #[test]
+    fn solve_finds_mate_in_one() {
+        let pos: Position = "k7/8/8/8/8/8/1R6/2R3K1 w - - 0 1".parse().unwrap();
+        let s = MateSolver::default()
+            .solve(&pos, 3, Control::Unlimited)
+            .unwrap()
+            .unwrap();
+        assert_eq!(s.attack.to_string(), "c1a1");
+        assert_eq!(s.moves(), 1);
+    }
+
+    #[test]
+    fn solve_finds_mate_in_two() {
+        let fen = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+        let pos: Position = fen.parse().unwrap();
+        let mut solver = MateSolver::default();
+        assert_eq!(solver.solve(&pos, 1, Control::Unlimited), Ok(None));
+
+        let s = solver.solve(&pos, 2, Control::Unlimited).unwrap().unwrap();
+        assert_eq!(s.attack.to_string(), "d5f6");
+        assert_eq!(s.moves(), 2);
+        verify(&pos, &s);
+    }
+
+    #[test]
+    fn solve_disproves_mate_from_starting_position() {
+        let pos = Position::default();
+        assert_eq!(
+            MateSolver::default().solve(&pos, 2, Control::Unlimited),
+            Ok(None)
+        );
+    }
+}
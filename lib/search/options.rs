@@ -1,6 +1,6 @@
-use crate::util::Integer;
+use crate::{search::Params, util::Integer};
 use derive_more::{Debug, Display, Error, Shl, Shr};
-use std::{cmp::Ordering, str::FromStr};
+use std::{cmp::Ordering, str::FromStr, time::Duration};
 
 /// The hash size in bytes.
 #[derive(Debug, Display, Copy, Clone, Eq, Ord, Hash, Shl, Shr)]
@@ -120,7 +120,7 @@ impl FromStr for ThreadCount {
 }
 
 /// Configuration for adversarial search algorithms.
-#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(test, derive(test_strategy::Arbitrary))]
 pub struct Options {
     /// The size of the transposition table in bytes.
@@ -130,6 +130,28 @@ pub struct Options {
 
     /// The number of threads to use while searching.
     pub threads: ThreadCount,
+
+    /// The time reserved on every move to compensate for communication latency.
+    pub overhead: Duration,
+
+    /// Whether searches limited by nodes are reproducible for a given number of threads.
+    pub deterministic: bool,
+
+    /// The tunable search parameters.
+    #[cfg_attr(test, strategy(proptest::strategy::Just(Params::default())))]
+    pub params: Params,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            hash: HashSize::default(),
+            threads: ThreadCount::default(),
+            overhead: Duration::from_millis(10),
+            deterministic: false,
+            params: Params::default(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,158 @@
+/// The declaration of a tunable search parameter.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Param {
+    /// The name of the parameter.
+    pub name: &'static str,
+    /// The compiled default value.
+    pub default: i16,
+    /// The smallest value the parameter may take.
+    pub min: i16,
+    /// The largest value the parameter may take.
+    pub max: i16,
+    /// The typical magnitude of a perturbation while tuning.
+    pub step: i16,
+}
+
+macro_rules! params {
+    ($($(#[$doc:meta])* $name:ident: $default:literal in $min:literal..=$max:literal step $step:literal,)*) => {
+        /// The tunable search parameters.
+        ///
+        /// Unless the `spsa` feature is enabled, this is a zero-sized type
+        /// and every parameter is a compile-time constant.
+        #[cfg(not(feature = "spsa"))]
+        #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+        pub struct Params(());
+
+        /// The tunable search parameters.
+        #[cfg(feature = "spsa")]
+        #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        pub struct Params {
+            $($name: i16,)*
+        }
+
+        #[cfg(feature = "spsa")]
+        impl Default for Params {
+            fn default() -> Self {
+                Params {
+                    $($name: $default,)*
+                }
+            }
+        }
+
+        impl Params {
+            /// The declarations of every tunable parameter.
+            pub const LIST: &[Param] = &[$(Param {
+                name: stringify!($name),
+                default: $default,
+                min: $min,
+                max: $max,
+                step: $step,
+            },)*];
+
+            $(
+                $(#[$doc])*
+                #[cfg(not(feature = "spsa"))]
+                #[inline(always)]
+                pub const fn $name(&self) -> i16 {
+                    $default
+                }
+
+                $(#[$doc])*
+                #[cfg(feature = "spsa")]
+                #[inline(always)]
+                pub fn $name(&self) -> i16 {
+                    self.$name
+                }
+            )*
+
+            /// The current value of the parameter called `name`.
+            pub fn get(&self, name: &str) -> Option<i16> {
+                match name {
+                    $(stringify!($name) => Some(self.$name()),)*
+                    _ => None,
+                }
+            }
+
+            /// Changes the value of the parameter called `name`, clamped to its range.
+            ///
+            /// Returns `false` if there is no such parameter.
+            #[cfg(feature = "spsa")]
+            pub fn set(&mut self, name: &str, value: i16) -> bool {
+                match name {
+                    $(stringify!($name) => self.$name = value.clamp($min, $max),)*
+                    _ => return false,
+                }
+
+                true
+            }
+        }
+    };
+}
+
+params! {
+    /// The constant reduction of null move pruning.
+    nmp_reduction: 3 in 1..=6 step 1,
+    /// The divisor of the draft-dependent reduction of null move pruning.
+    nmp_divisor: 4 in 2..=8 step 1,
+    /// The singular margin per ply of draft, which also gates multi-cut pruning.
+    se_margin: 2 in 1..=6 step 1,
+    /// The additional singular margin required to extend by two plies.
+    se_double: 30 in 0..=100 step 5,
+    /// The margin by which ProbCut raises beta.
+    pc_margin: 200 in 50..=400 step 15,
+    /// The offset added to the deficit before razoring.
+    razor_offset: 30 in 0..=100 step 5,
+    /// The deficit per ply of razoring.
+    razor_divisor: 210 in 100..=400 step 15,
+    /// The offset added to the surplus before reverse futility pruning.
+    rfp_offset: 60 in 0..=120 step 5,
+    /// The surplus per ply of reverse futility pruning.
+    rfp_divisor: 140 in 60..=300 step 10,
+    /// The offset added to the deficit before futility pruning.
+    fp_offset: 30 in 0..=80 step 5,
+    /// The deficit per ply of futility pruning.
+    fp_divisor: 40 in 20..=120 step 5,
+    /// The margin of delta pruning.
+    dp_margin: 200 in 50..=400 step 15,
+    /// The divisor of late move reductions, in hundredths.
+    lmr_divisor: 300 in 150..=600 step 20,
+    /// The initial half-width of the aspiration window.
+    aw_delta: 5 in 1..=40 step 2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::sample::select;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn default_is_within_range(#[strategy(select(Params::LIST))] p: Param) {
+        assert!((p.min..=p.max).contains(&p.default));
+    }
+
+    #[proptest]
+    fn step_is_positive_and_smaller_than_range(#[strategy(select(Params::LIST))] p: Param) {
+        assert!(p.step > 0);
+        assert!(p.step <= p.max - p.min);
+    }
+
+    #[proptest]
+    fn get_returns_default_value(#[strategy(select(Params::LIST))] p: Param) {
+        assert_eq!(Params::default().get(p.name), Some(p.default));
+    }
+
+    #[cfg(feature = "spsa")]
+    #[proptest]
+    fn set_clamps_value_to_range(#[strategy(select(Params::LIST))] p: Param, v: i16) {
+        let mut params = Params::default();
+        assert!(params.set(p.name, v));
+        assert_eq!(params.get(p.name), Some(v.clamp(p.min, p.max)));
+    }
+
+    #[cfg(feature = "spsa")]
+    #[proptest]
+    fn set_fails_for_unknown_parameter(v: i16) {
+        assert!(!Params::default().set("foo", v));
+    }
+}
@@ -0,0 +1,187 @@
+use derive_more::Display;
+
+#[cfg(feature = "stats")]
+use std::fmt::{self, Formatter};
+#[cfg(feature = "stats")]
+use std::ops::AddAssign;
+
+/// An event counted by [`Stats`].
+#[derive(Debug, Display, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+#[repr(u8)]
+pub enum Stat {
+    /// A node visited by the full-width search.
+    #[display("nodes")]
+    Node,
+    /// A node visited by the quiescence search.
+    #[display("qnodes")]
+    QsNode,
+    /// A transposition found for a node.
+    #[display("tthits")]
+    TtHit,
+    /// A node cut off by a transposition.
+    #[display("ttcuts")]
+    TtCutoff,
+    /// A node pruned by null move pruning.
+    #[display("nmp")]
+    NmpPrune,
+    /// A node pruned by reverse futility pruning.
+    #[display("rfp")]
+    RfpPrune,
+    /// A node pruned by razoring.
+    #[display("razor")]
+    RazorPrune,
+    /// A node pruned by multi-cut pruning.
+    #[display("mcp")]
+    McpPrune,
+    /// A node pruned by ProbCut.
+    #[display("pc")]
+    PcPrune,
+    /// The remaining moves of a node pruned by futility pruning.
+    #[display("fp")]
+    FpPrune,
+    /// A move pruned by late move pruning.
+    #[display("lmp")]
+    LmpPrune,
+    /// A move searched again after a reduced search failed high.
+    #[display("researches")]
+    LmrResearch,
+    /// A beta cutoff.
+    #[display("cutoffs")]
+    Cutoff,
+    /// A beta cutoff caused by the first move searched.
+    #[display("firstcuts")]
+    FirstMoveCutoff,
+}
+
+impl Stat {
+    /// Every kind of event.
+    pub const ALL: [Stat; 14] = [
+        Stat::Node,
+        Stat::QsNode,
+        Stat::TtHit,
+        Stat::TtCutoff,
+        Stat::NmpPrune,
+        Stat::RfpPrune,
+        Stat::RazorPrune,
+        Stat::McpPrune,
+        Stat::PcPrune,
+        Stat::FpPrune,
+        Stat::LmpPrune,
+        Stat::LmrResearch,
+        Stat::Cutoff,
+        Stat::FirstMoveCutoff,
+    ];
+}
+
+/// Counters of search events.
+///
+/// Unless the `stats` feature is enabled, this is a zero-sized type and counting is a no-op.
+#[cfg(not(feature = "stats"))]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Stats(());
+
+/// Counters of search events.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct Stats(
+    #[cfg_attr(test, map(|a: [u32; Stat::ALL.len()]| a.map(u64::from)))] [u64; Stat::ALL.len()],
+);
+
+impl Stats {
+    /// Counts an occurrence of `stat`.
+    #[cfg(not(feature = "stats"))]
+    #[inline(always)]
+    pub fn count(&mut self, _: Stat) {}
+
+    /// Counts an occurrence of `stat`.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn count(&mut self, stat: Stat) {
+        self.0[stat as usize] += 1;
+    }
+
+    /// The number of occurrences of `stat`.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn get(&self, stat: Stat) -> u64 {
+        self.0[stat as usize]
+    }
+
+    /// The number of occurrences of `a` per occurrence of `b`.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn ratio(&self, a: Stat, b: Stat) -> f64 {
+        self.get(a) as f64 / self.get(b).max(1) as f64
+    }
+}
+
+#[cfg(feature = "stats")]
+impl AddAssign for Stats {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: Self) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a += b;
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut stats = Stat::ALL.iter();
+        if let Some(&s) = stats.next() {
+            write!(f, "{s} {}", self.get(s))?;
+        }
+
+        for &s in stats {
+            write!(f, " {s} {}", self.get(s))?;
+        }
+
+        let nodes = self.get(Stat::Node) + self.get(Stat::QsNode);
+        let qs = self.get(Stat::QsNode) as f64 / nodes.max(1) as f64 * 100.;
+        let tt = self.get(Stat::TtHit) as f64 / nodes.max(1) as f64 * 100.;
+        let first = self.ratio(Stat::FirstMoveCutoff, Stat::Cutoff) * 100.;
+        write!(
+            f,
+            " qshare {qs:.1}% tthitrate {tt:.1}% firstcutrate {first:.1}%"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[cfg(not(feature = "stats"))]
+    #[test]
+    fn stats_are_zero_sized() {
+        assert_eq!(size_of::<Stats>(), 0);
+    }
+
+    #[proptest]
+    fn every_stat_is_listed_in_order(s: Stat) {
+        assert_eq!(Stat::ALL[s as usize], s);
+    }
+
+    #[cfg(feature = "stats")]
+    #[proptest]
+    fn count_increments_stat(mut stats: Stats, s: Stat) {
+        let before = stats;
+        stats.count(s);
+
+        for t in Stat::ALL {
+            assert_eq!(stats.get(t), before.get(t) + (s == t) as u64);
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[proptest]
+    fn stats_are_aggregated_by_addition(a: Stats, b: Stats, s: Stat) {
+        let mut c = a;
+        c += b;
+        assert_eq!(c.get(s), a.get(s) + b.get(s));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::chess::Move;
+use crate::search::{Pv, Score};
+use crate::util::Integer;
+use std::{ops::Range, time::Duration};
+
+/// A [time manager] that adapts the time allotted to a search as it progresses.
+///
+/// The soft limit shrinks while the best move stays the same and grows when
+/// the best move changes or the score drops, but it never exceeds the hard limit.
+///
+/// [time manager]: https://www.chessprogramming.org/Time_Management
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+    scale: f64,
+    best: Option<Move>,
+    stability: u32,
+    score: Option<Score>,
+}
+
+impl TimeManager {
+    /// Constructs a time manager given the soft and hard limits.
+    #[inline(always)]
+    pub fn new(time: Range<Duration>) -> Self {
+        TimeManager {
+            soft: time.start.min(time.end),
+            hard: time.end,
+            scale: 1.,
+            best: None,
+            stability: 0,
+            score: None,
+        }
+    }
+
+    /// The time after which no further iterations should be started.
+    #[inline(always)]
+    pub fn limit(&self) -> Duration {
+        if self.soft >= self.hard {
+            return self.hard;
+        }
+
+        let soft = self.soft.as_secs_f64() * self.scale;
+        Duration::try_from_secs_f64(soft).map_or(self.hard, |s| s.min(self.hard))
+    }
+
+    /// Adjusts the soft limit once an iteration completes.
+    ///
+    /// The `effort` is the fraction of all nodes spent searching the best move.
+    pub fn update<const N: usize>(&mut self, pv: &Pv<N>, effort: f64) {
+        let best = pv.iter().next();
+        self.stability = match self.best {
+            Some(_) if best == self.best => self.stability.saturating_add(1),
+            _ => 0,
+        };
+
+        let drop = match self.score {
+            None => 0.,
+            Some(s) => (s.get() as f64 - pv.score().get() as f64).clamp(0., 200.),
+        };
+
+        let stability = 1.3 - 0.1 * self.stability.min(6) as f64;
+        let falling = 1. + drop / 200.;
+        let effort = 1.6 - effort.clamp(0., 1.);
+
+        self.scale = stability * falling * effort;
+        self.best = best;
+        self.score = Some(pv.score());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::Line;
+    use proptest::{collection::vec, prelude::*};
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn limit_never_exceeds_hard_limit(
+        #[strategy(..=#h)] s: u64,
+        h: u64,
+        #[strategy(vec(any::<(Pv, f64)>(), ..8))] iterations: Vec<(Pv, f64)>,
+    ) {
+        let (s, h) = (Duration::from_millis(s), Duration::from_millis(h));
+        let mut tm = TimeManager::new(s..h);
+        assert!(tm.limit() <= h);
+
+        for (pv, effort) in iterations {
+            tm.update(&pv, effort);
+            assert!(tm.limit() <= h);
+        }
+    }
+
+    #[proptest]
+    fn limit_is_fixed_if_soft_limit_equals_hard_limit(t: Duration, pv: Pv, effort: f64) {
+        let mut tm = TimeManager::new(t..t);
+        tm.update(&pv, effort);
+        assert_eq!(tm.limit(), t);
+    }
+
+    #[proptest]
+    fn limit_shrinks_while_best_move_is_stable(
+        #[strategy(1u64..1000)] s: u64,
+        #[filter(#pv.head().is_some())] pv: Pv,
+    ) {
+        let soft = Duration::from_millis(s);
+        let mut tm = TimeManager::new(soft..Duration::MAX);
+        tm.update(&pv, 0.5);
+        let before = tm.limit();
+        tm.update(&pv, 0.5);
+        assert!(tm.limit() < before);
+    }
+
+    #[proptest]
+    fn limit_grows_if_best_move_changes(
+        #[strategy(1u64..1000)] s: u64,
+        score: Score,
+        a: Move,
+        #[filter(#a != #b)] b: Move,
+    ) {
+        let soft = Duration::from_millis(s);
+        let mut tm = TimeManager::new(soft..Duration::MAX);
+        let pv = |m| -> Pv { Pv::new(score, Line::singular(m)) };
+
+        tm.update(&pv(a), 0.5);
+        tm.update(&pv(a), 0.5);
+        let before = tm.limit();
+        tm.update(&pv(b), 0.5);
+        assert!(tm.limit() > before);
+    }
+
+    #[proptest]
+    fn limit_grows_if_score_drops(
+        #[strategy(1u64..1000)] s: u64,
+        #[filter(#pv.head().is_some())] pv: Pv,
+        #[strategy(1i16..100)] d: i16,
+    ) {
+        let soft = Duration::from_millis(s);
+        let mut tm = TimeManager::new(soft..Duration::MAX);
+        let dropped = Pv::new(pv.score() - d, pv.moves().clone());
+
+        let mut stable = tm.clone();
+        tm.update(&pv, 0.5);
+        stable.update(&pv, 0.5);
+        tm.update(&dropped, 0.5);
+        stable.update(&pv, 0.5);
+        prop_assume!(dropped.score() < pv.score());
+        assert!(tm.limit() > stable.limit());
+    }
+
+    #[proptest]
+    fn limit_shrinks_as_effort_on_best_move_grows(
+        #[strategy(1u64..1000)] s: u64,
+        pv: Pv,
+        #[strategy(0f64..0.99)] e: f64,
+        #[strategy(#e + 0.01..=1.)] f: f64,
+    ) {
+        let soft = Duration::from_millis(s);
+        let (mut a, mut b) = (
+            TimeManager::new(soft..Duration::MAX),
+            TimeManager::new(soft..Duration::MAX),
+        );
+        a.update(&pv, e);
+        b.update(&pv, f);
+        assert!(b.limit() <= a.limit());
+    }
+}
@@ -0,0 +1,612 @@
+use crate::nnue::Value;
+use crate::search::{Depth, Ply, Score, ScoreBound};
+use crate::uci::UciMove;
+use derive_more::{Display, Error};
+use std::fmt::{self, Formatter};
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+#[cfg(feature = "trace")]
+use crate::util::Integer;
+#[cfg(feature = "trace")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "trace")]
+use std::sync::{Arc, Mutex, PoisonError};
+#[cfg(feature = "trace")]
+use std::{fs::File, path::Path};
+
+#[cfg(test)]
+use proptest::{collection::vec, prelude::*};
+
+/// A decision taken by the full-width search at a node.
+#[derive(Debug, Display, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+#[repr(u8)]
+pub enum Decision {
+    /// The node was cut off by a transposition.
+    #[display("ttcut")]
+    TtCutoff,
+    /// The node was pruned by razoring.
+    #[display("razor")]
+    Razor,
+    /// The node was pruned by reverse futility pruning.
+    #[display("rfp")]
+    Rfp,
+    /// The node was pruned by null move pruning.
+    #[display("nmp")]
+    Nmp,
+    /// The node was pruned by ProbCut.
+    #[display("pc")]
+    ProbCut,
+    /// The node was pruned by multi-cut pruning.
+    #[display("mcp")]
+    MultiCut,
+    /// The node was extended for being in check.
+    #[display("check")]
+    CheckExtension,
+    /// The node was reduced for lack of a transposition.
+    #[display("iir")]
+    Iir,
+    /// The best move was extended for being singular.
+    #[display("singular")]
+    SingularExtension,
+    /// The best move was extended twice for being singular by a wide margin.
+    #[display("double")]
+    DoubleExtension,
+    /// The best move was reduced for not being singular.
+    #[display("negative")]
+    NegativeExtension,
+    /// The remaining moves were pruned by futility pruning.
+    #[display("fp")]
+    Fp,
+    /// Some moves were pruned by late move pruning.
+    #[display("lmp")]
+    Lmp,
+    /// Some moves were searched to a reduced depth.
+    #[display("lmr")]
+    Lmr,
+    /// Some moves were searched again after a reduced search failed high.
+    #[display("research")]
+    Research,
+}
+
+impl Decision {
+    /// Every kind of decision.
+    pub const ALL: [Decision; 15] = [
+        Decision::TtCutoff,
+        Decision::Razor,
+        Decision::Rfp,
+        Decision::Nmp,
+        Decision::ProbCut,
+        Decision::MultiCut,
+        Decision::CheckExtension,
+        Decision::Iir,
+        Decision::SingularExtension,
+        Decision::DoubleExtension,
+        Decision::NegativeExtension,
+        Decision::Fp,
+        Decision::Lmp,
+        Decision::Lmr,
+        Decision::Research,
+    ];
+}
+
+/// The reason why parsing a [`Decision`] failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error)]
+#[display("failed to parse search decision")]
+pub struct ParseDecisionError;
+
+impl FromStr for Decision {
+    type Err = ParseDecisionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut decisions = Decision::ALL.into_iter();
+        decisions
+            .find(|d| d.to_string() == s)
+            .ok_or(ParseDecisionError)
+    }
+}
+
+/// The transposition found at a traced node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct TracedTransposition {
+    /// The score bound, normalized to the ply of the node.
+    pub score: ScoreBound,
+    /// The depth searched.
+    pub draft: Depth,
+    /// The best move.
+    pub best: UciMove,
+}
+
+/// A node visited by the full-width search, as recorded in a trace.
+///
+/// Nodes are recorded in the order they are left, so every node is preceded by its descendants.
+/// Each node is printed on its own line as a sequence of keys followed by their values, e.g.
+/// `thread 0 ply 1 path e2e4 alpha -20 beta 15 draft 3 eval 23 tt lower 30 2 g1f3 decisions rfp score 30`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct TraceNode {
+    /// The search thread that visited the node.
+    #[cfg_attr(test, strategy(..64usize))]
+    pub thread: usize,
+    /// The number of plies from the root.
+    pub ply: Ply,
+    /// The moves played from the root, where [`None`] stands for the null move.
+    #[cfg_attr(test, strategy(vec(any::<Option<UciMove>>(), ..4)))]
+    pub path: Vec<Option<UciMove>>,
+    /// The lower bound of the search window.
+    pub alpha: Score,
+    /// The upper bound of the search window.
+    pub beta: Score,
+    /// The remaining depth.
+    pub draft: Depth,
+    /// The static evaluation.
+    pub eval: Value,
+    /// The transposition found, if any.
+    pub tt: Option<TracedTransposition>,
+    /// The decisions taken at the node.
+    #[cfg_attr(test, strategy(vec(any::<Decision>(), ..4)))]
+    pub decisions: Vec<Decision>,
+    /// The score returned.
+    pub score: Score,
+}
+
+impl fmt::Display for TraceNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "thread {} ply {} path", self.thread, self.ply)?;
+        for m in &self.path {
+            match m {
+                None => f.write_str(" 0000")?,
+                Some(m) => write!(f, " {m}")?,
+            }
+        }
+
+        write!(f, " alpha {} beta {}", self.alpha, self.beta)?;
+        write!(f, " draft {} eval {}", self.draft, self.eval)?;
+
+        match self.tt {
+            None => f.write_str(" tt none")?,
+            Some(t) => {
+                let (kind, score) = match t.score {
+                    ScoreBound::Lower(s) => ("lower", s),
+                    ScoreBound::Upper(s) => ("upper", s),
+                    ScoreBound::Exact(s) => ("exact", s),
+                };
+
+                write!(f, " tt {kind} {score} {} {}", t.draft, t.best)?;
+            }
+        }
+
+        f.write_str(" decisions")?;
+        for d in &self.decisions {
+            write!(f, " {d}")?;
+        }
+
+        write!(f, " score {}", self.score)
+    }
+}
+
+/// The reason why parsing a [`TraceNode`] failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error)]
+#[display("failed to parse trace node")]
+pub struct ParseTraceNodeError;
+
+impl FromStr for TraceNode {
+    type Err = ParseTraceNodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn value<T: FromStr>(token: Option<&str>) -> Result<T, ParseTraceNodeError> {
+            token
+                .ok_or(ParseTraceNodeError)?
+                .parse()
+                .map_err(|_| ParseTraceNodeError)
+        }
+
+        fn key<'a>(
+            tokens: &mut impl Iterator<Item = &'a str>,
+            k: &str,
+        ) -> Result<(), ParseTraceNodeError> {
+            match tokens.next() {
+                Some(t) if t == k => Ok(()),
+                _ => Err(ParseTraceNodeError),
+            }
+        }
+
+        let mut tokens = s.split_whitespace().peekable();
+
+        key(&mut tokens, "thread")?;
+        let thread = value(tokens.next())?;
+        key(&mut tokens, "ply")?;
+        let ply = value(tokens.next())?;
+        key(&mut tokens, "path")?;
+
+        let mut path = Vec::new();
+        while let Some(&t) = tokens.peek().filter(|&&t| t != "alpha") {
+            path.push(match t {
+                "0000" => None,
+                m => Some(value(Some(m))?),
+            });
+
+            tokens.next();
+        }
+
+        key(&mut tokens, "alpha")?;
+        let alpha = value(tokens.next())?;
+        key(&mut tokens, "beta")?;
+        let beta = value(tokens.next())?;
+        key(&mut tokens, "draft")?;
+        let draft = value(tokens.next())?;
+        key(&mut tokens, "eval")?;
+        let eval = value(tokens.next())?;
+        key(&mut tokens, "tt")?;
+
+        let tt = match tokens.next() {
+            Some("none") => None,
+            kind => {
+                let score = value(tokens.next())?;
+                let score = match kind {
+                    Some("lower") => ScoreBound::Lower(score),
+                    Some("upper") => ScoreBound::Upper(score),
+                    Some("exact") => ScoreBound::Exact(score),
+                    _ => return Err(ParseTraceNodeError),
+                };
+
+                let draft = value(tokens.next())?;
+                let best = value(tokens.next())?;
+                Some(TracedTransposition { score, draft, best })
+            }
+        };
+
+        key(&mut tokens, "decisions")?;
+
+        let mut decisions = Vec::new();
+        while let Some(&t) = tokens.peek().filter(|&&t| t != "score") {
+            decisions.push(value(Some(t))?);
+            tokens.next();
+        }
+
+        key(&mut tokens, "score")?;
+        let score = value(tokens.next())?;
+
+        if tokens.next().is_some() {
+            return Err(ParseTraceNodeError);
+        }
+
+        Ok(TraceNode {
+            thread,
+            ply,
+            path,
+            alpha,
+            beta,
+            draft,
+            eval,
+            tt,
+            decisions,
+            score,
+        })
+    }
+}
+
+/// Selects which [`TraceNode`]s to replay.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct TraceFilter {
+    #[cfg_attr(test, strategy(vec(any::<Option<UciMove>>(), ..4)))]
+    path: Vec<Option<UciMove>>,
+    descendants: bool,
+    thread: Option<usize>,
+    decision: Option<Decision>,
+}
+
+impl TraceFilter {
+    /// Selects the nodes reached by playing exactly the moves in `path` from the root.
+    pub fn at(path: impl IntoIterator<Item = Option<UciMove>>) -> Self {
+        TraceFilter {
+            path: path.into_iter().collect(),
+            ..TraceFilter::default()
+        }
+    }
+
+    /// Selects the nodes reached through `path`, including their descendants.
+    pub fn under(path: impl IntoIterator<Item = Option<UciMove>>) -> Self {
+        TraceFilter {
+            descendants: true,
+            ..Self::at(path)
+        }
+    }
+
+    /// Further restricts the selection to nodes visited by the `id`-th search thread.
+    pub fn thread(self, id: usize) -> Self {
+        TraceFilter {
+            thread: Some(id),
+            ..self
+        }
+    }
+
+    /// Further restricts the selection to nodes where a given decision was taken.
+    pub fn decision(self, decision: Decision) -> Self {
+        TraceFilter {
+            decision: Some(decision),
+            ..self
+        }
+    }
+
+    /// Whether `node` is selected.
+    pub fn matches(&self, node: &TraceNode) -> bool {
+        let path = match self.descendants {
+            true => node.path.starts_with(&self.path),
+            false => node.path == self.path,
+        };
+
+        path && self.thread.is_none_or(|id| node.thread == id)
+            && self.decision.is_none_or(|d| node.decisions.contains(&d))
+    }
+}
+
+/// Reads [`TraceNode`]s recorded in a trace, one per line.
+#[derive(Debug)]
+pub struct TraceReader<R> {
+    lines: io::Lines<R>,
+}
+
+impl<R: BufRead> TraceReader<R> {
+    /// Reads a trace from `reader`.
+    pub fn new(reader: R) -> Self {
+        TraceReader {
+            lines: reader.lines(),
+        }
+    }
+
+    /// Only reads the nodes selected by `filter`.
+    pub fn filter(self, filter: TraceFilter) -> impl Iterator<Item = io::Result<TraceNode>> {
+        Iterator::filter(self, move |n| {
+            n.as_ref().is_err() || n.as_ref().is_ok_and(|n| filter.matches(n))
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+    type Item = io::Result<TraceNode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.lines.next()? {
+            Err(e) => return Some(Err(e)),
+            Ok(line) => line,
+        };
+
+        let node = line.parse();
+        Some(node.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+}
+
+/// A file the search tree is recorded into.
+///
+/// Only nodes up to a given ply are recorded, at most a given number of them by each thread.
+#[cfg(feature = "trace")]
+#[derive(Debug, Clone)]
+pub struct Trace {
+    writer: Arc<Mutex<BufWriter<File>>>,
+    plies: Ply,
+    nodes: u64,
+}
+
+#[cfg(feature = "trace")]
+impl Trace {
+    /// Creates a trace file at `path`, truncating it if it already exists.
+    pub fn create(path: impl AsRef<Path>, plies: Ply, nodes: u64) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Trace {
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+            plies,
+            nodes,
+        })
+    }
+
+    /// Appends nodes to the trace file.
+    fn write(&self, nodes: &[TraceNode]) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        for node in nodes {
+            writeln!(writer, "{node}")?;
+        }
+
+        writer.flush()
+    }
+}
+
+/// Records the nodes visited by a search thread.
+///
+/// Unless the `trace` feature is enabled, this is a zero-sized type and recording is a no-op.
+#[cfg(not(feature = "trace"))]
+#[derive(Debug, Default, Clone)]
+pub struct Tracer(());
+
+#[cfg(not(feature = "trace"))]
+impl Tracer {
+    /// Starts recording the decisions taken at a new node.
+    #[inline(always)]
+    pub fn enter(&mut self) {}
+
+    /// Records a decision taken at the current node.
+    #[inline(always)]
+    pub fn decide(&mut self, _: Decision) {}
+}
+
+/// Records the nodes visited by a search thread.
+#[cfg(feature = "trace")]
+#[derive(Debug, Default, Clone)]
+pub struct Tracer {
+    trace: Option<Trace>,
+    stack: Vec<u16>,
+    nodes: Vec<TraceNode>,
+}
+
+#[cfg(feature = "trace")]
+impl Tracer {
+    /// Records into `trace`, if any.
+    pub fn new(trace: Option<Trace>) -> Self {
+        Tracer {
+            trace,
+            ..Tracer::default()
+        }
+    }
+
+    /// Starts recording the decisions taken at a new node.
+    #[inline(always)]
+    pub fn enter(&mut self) {
+        self.stack.push(0);
+    }
+
+    /// Records a decision taken at the current node.
+    #[inline(always)]
+    pub fn decide(&mut self, decision: Decision) {
+        if let Some(d) = self.stack.last_mut() {
+            *d |= 1 << decision as u16;
+        }
+    }
+
+    /// Stops recording the current node, returning the decisions taken since it was entered.
+    pub fn leave(&mut self) -> Vec<Decision> {
+        let bits = self.stack.pop().unwrap_or(0);
+        let decisions = Decision::ALL.into_iter();
+        decisions.filter(|&d| bits & (1 << d as u16) != 0).collect()
+    }
+
+    /// Whether a node at `ply` would be recorded.
+    pub fn is_recording(&self, ply: Ply) -> bool {
+        self.trace.as_ref().is_some_and(|t| {
+            (0..=t.plies.get()).contains(&ply.get()) && (self.nodes.len() as u64) < t.nodes
+        })
+    }
+
+    /// Records a node, if still within bounds.
+    pub fn record(&mut self, node: TraceNode) {
+        if self.is_recording(node.ply) {
+            self.nodes.push(node);
+        }
+    }
+
+    /// Writes the nodes recorded so far into the trace file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.stack.clear();
+        if let Some(trace) = &self.trace {
+            trace.write(&self.nodes)?;
+        }
+
+        self.nodes.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn parsing_printed_decision_is_an_identity(d: Decision) {
+        assert_eq!(d.to_string().parse(), Ok(d));
+    }
+
+    #[proptest]
+    fn every_decision_is_listed_in_order(d: Decision) {
+        assert_eq!(Decision::ALL[d as usize], d);
+    }
+
+    #[proptest]
+    fn parsing_printed_trace_node_is_an_identity(n: TraceNode) {
+        assert_eq!(n.to_string().parse(), Ok(n));
+    }
+
+    #[proptest]
+    fn parsing_trace_node_fails_for_trailing_tokens(n: TraceNode) {
+        let s = format!("{n} score 0");
+        assert_eq!(s.parse::<TraceNode>(), Err(ParseTraceNodeError));
+    }
+
+    #[proptest]
+    fn filter_at_path_matches_only_that_node(#[filter(!#n.path.is_empty())] n: TraceNode) {
+        assert!(TraceFilter::at(n.path.clone()).matches(&n));
+        assert!(!TraceFilter::at(n.path[..n.path.len() - 1].to_vec()).matches(&n));
+    }
+
+    #[proptest]
+    fn filter_under_path_matches_descendants(n: TraceNode, #[strategy(..=#n.path.len())] i: usize) {
+        assert!(TraceFilter::under(n.path[..i].to_vec()).matches(&n));
+    }
+
+    #[proptest]
+    fn filter_by_thread_matches_nodes_of_that_thread(
+        n: TraceNode,
+        #[strategy(..64usize)] id: usize,
+    ) {
+        let filter = TraceFilter::under([]).thread(id);
+        assert_eq!(filter.matches(&n), n.thread == id);
+    }
+
+    #[proptest]
+    fn filter_by_decision_matches_nodes_where_it_was_taken(n: TraceNode, d: Decision) {
+        let filter = TraceFilter::under([]).decision(d);
+        assert_eq!(filter.matches(&n), n.decisions.contains(&d));
+    }
+
+    #[proptest]
+    fn reader_reads_nodes_in_order(#[strategy(vec(any::<TraceNode>(), ..8))] ns: Vec<TraceNode>) {
+        let trace: String = ns.iter().map(|n| format!("{n}\n")).collect();
+        let reader = TraceReader::new(Cursor::new(trace));
+        assert_eq!(reader.map(Result::unwrap).collect::<Vec<_>>(), ns);
+    }
+
+    #[proptest]
+    fn reader_skips_nodes_not_selected(
+        #[strategy(vec(any::<TraceNode>(), ..8))] ns: Vec<TraceNode>,
+        f: TraceFilter,
+    ) {
+        let trace: String = ns.iter().map(|n| format!("{n}\n")).collect();
+        let reader = TraceReader::new(Cursor::new(trace)).filter(f.clone());
+        let expected: Vec<_> = ns.into_iter().filter(|n| f.matches(n)).collect();
+        assert_eq!(reader.map(Result::unwrap).collect::<Vec<_>>(), expected);
+    }
+
+    #[proptest]
+    fn reader_fails_for_invalid_lines(#[filter(#s.parse::<TraceNode>().is_err())] s: String) {
+        let mut reader = TraceReader::new(Cursor::new(s.replace('\n', " ")));
+        assert!(reader.next().is_none_or(|n| n.is_err()));
+    }
+
+    #[cfg(feature = "trace")]
+    #[proptest]
+    fn tracer_records_decisions_since_node_was_entered(a: Decision, b: Decision) {
+        let mut tracer = Tracer::default();
+        tracer.enter();
+        tracer.decide(a);
+        tracer.enter();
+        tracer.decide(b);
+        assert_eq!(tracer.leave(), vec![b]);
+        assert_eq!(tracer.leave(), vec![a]);
+    }
+
+    #[cfg(feature = "trace")]
+    #[proptest]
+    fn tracer_records_nodes_within_bounds(
+        #[strategy(vec(any::<TraceNode>(), ..8))] ns: Vec<TraceNode>,
+        plies: Ply,
+        #[strategy(..8u64)] nodes: u64,
+    ) {
+        let path = std::env::temp_dir().join(format!("cinder-tracer-{}.trace", std::process::id()));
+        let mut tracer = Tracer::new(Some(Trace::create(&path, plies, nodes)?));
+        for n in ns.iter().cloned() {
+            tracer.record(n);
+        }
+
+        tracer.flush()?;
+        let file = io::BufReader::new(File::open(&path)?);
+        let recorded: Vec<_> = TraceReader::new(file).map(Result::unwrap).collect();
+
+        let expected = ns
+            .into_iter()
+            .filter(|n| (0..=plies.get()).contains(&n.ply.get()));
+        assert_eq!(recorded, expected.take(nodes as usize).collect::<Vec<_>>());
+    }
+}
@@ -1,10 +1,10 @@
 use crate::chess::{Move, Zobrist};
-use crate::search::{Depth, HashSize, Line, Ply, Pv, Score};
+use crate::search::{Depth, HashSize, Line, Ply, Pv, Score, ThreadCount};
 use crate::util::{Assume, Binary, Bits, Integer};
 use derive_more::Debug;
 use std::ops::{Index, Range, RangeInclusive};
 use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
-use std::{hint::unreachable_unchecked, mem::size_of};
+use std::{hint::unreachable_unchecked, mem::size_of, thread};
 
 #[cfg(test)]
 use crate::chess::Position;
@@ -197,37 +197,123 @@ impl Binary for SignedTransposition {
 
 /// A cache for [`Transposition`]s.
 #[derive(Debug)]
-#[cfg_attr(test, derive(test_strategy::Arbitrary))]
 #[debug("TranspositionTable({})", self.capacity())]
 pub struct TranspositionTable {
-    #[cfg_attr(test,
-        strategy(hash_map(any::<Position>(), any::<Transposition>(), ..32).prop_map(|ts| {
-            let mut cache: Box<[AtomicU64]> = (0..ts.len().next_power_of_two()).map(|_| AtomicU64::default()).collect();
-
-            for (pos, t) in ts {
-                let key = pos.zobrist();
-                let idx = key.slice(..cache.len().trailing_zeros()).cast::<usize>();
-                let sig = key.slice(cache.len().trailing_zeros()..).pop();
-                *cache[idx].get_mut() = Some(SignedTransposition(sig, t.encode())).encode().get();
-            }
-
-            cache
-        }))
-    )]
     cache: Box<[AtomicU64]>,
+    shift: u32,
+}
+
+#[cfg(test)]
+impl Arbitrary for TranspositionTable {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        hash_map(any::<Position>(), any::<Transposition>(), ..32)
+            .prop_map(|ts| {
+                let mut cache: Box<[AtomicU64]> = (0..ts.len().next_power_of_two())
+                    .map(|_| AtomicU64::default())
+                    .collect();
+
+                let shift = cache.len().trailing_zeros();
+                for (pos, t) in ts {
+                    let key = pos.zobrist();
+                    let idx = key.slice(..shift).cast::<usize>();
+                    let sig = key.slice(shift..).pop();
+                    *cache[idx].get_mut() =
+                        Some(SignedTransposition(sig, t.encode())).encode().get();
+                }
+
+                TranspositionTable { cache, shift }
+            })
+            .boxed()
+    }
 }
 
 impl TranspositionTable {
     const WIDTH: usize = size_of::<<Option<SignedTransposition> as Binary>::Bits>();
 
+    /// The minimum number of entries handled by each thread when sweeping the table.
+    const CHUNK: usize = 1 << 16;
+
+    #[inline(always)]
+    fn allocate(size: HashSize) -> Box<[AtomicU64]> {
+        let capacity = (1 + size.get() / 2).next_power_of_two() / Self::WIDTH;
+        unsafe { Box::new_zeroed_slice(capacity).assume_init() }
+    }
+
     /// Constructs a transposition table of at most `size` many bytes.
     #[inline(always)]
     pub fn new(size: HashSize) -> Self {
-        let capacity = (1 + size.get() / 2).next_power_of_two() / Self::WIDTH;
+        let cache = Self::allocate(size);
 
         TranspositionTable {
-            cache: (0..capacity).map(|_| AtomicU64::default()).collect(),
+            shift: cache.len().trailing_zeros(),
+            cache,
+        }
+    }
+
+    /// Discards all [`Transposition`]s, zeroing the table using up to `threads` many threads.
+    pub fn clear(&mut self, threads: ThreadCount) {
+        let chunk = self.capacity().div_ceil(threads.get()).max(Self::CHUNK);
+
+        thread::scope(|s| {
+            for entries in self.cache.chunks_mut(chunk) {
+                s.spawn(move || entries.iter_mut().for_each(|e| *e.get_mut() = 0));
+            }
+        });
+
+        self.shift = self.capacity().trailing_zeros();
+    }
+
+    /// Resizes this table to at most `size` many bytes using up to `threads` many threads.
+    ///
+    /// Existing [`Transposition`]s are rehashed into the new table whenever enough of their
+    /// original key is known. When shrinking, deeper transpositions are preferred on collisions.
+    pub fn resize(&mut self, size: HashSize, threads: ThreadCount) {
+        let cache = Self::allocate(size);
+        if cache.len() == self.capacity() {
+            return;
+        }
+
+        // Signatures are kept at the same offset while growing, so the bits they cover remain
+        // known and entries can later be rehashed again without loss.
+        let bits = cache.len().trailing_zeros();
+        let shift = self.shift.min(bits);
+        let known = self
+            .capacity()
+            .trailing_zeros()
+            .max(self.shift + Signature::BITS);
+
+        if self.capacity() > 0 && !cache.is_empty() && bits <= known {
+            let stride = cache.len().min(self.capacity());
+            let chunk = cache.len().div_ceil(threads.get()).max(Self::CHUNK);
+            let this = &*self;
+
+            thread::scope(|s| {
+                for (i, entries) in cache.chunks(chunk).enumerate() {
+                    s.spawn(move || {
+                        for (j, entry) in entries.iter().enumerate() {
+                            let idx = i * chunk + j;
+                            let best = (idx % stride..this.capacity())
+                                .step_by(stride)
+                                .filter_map(|k| this.load(k))
+                                .filter(|(key, _)| key.slice(..bits).cast::<usize>() == idx)
+                                .max_by_key(|(_, t)| t.draft());
+
+                            if let Some((key, t)) = best {
+                                let sig = key.slice(shift..).pop();
+                                let bits = Some(SignedTransposition(sig, t.encode())).encode();
+                                entry.store(bits.get(), Relaxed);
+                            }
+                        }
+                    });
+                }
+            });
         }
+
+        self.cache = cache;
+        self.shift = shift;
     }
 
     /// The actual size of this table in bytes.
@@ -282,7 +368,16 @@ impl TranspositionTable {
     /// Returns the [`Signature`] associated with `key`.
     #[inline(always)]
     pub fn sign(&self, key: Zobrist) -> Signature {
-        key.slice(self.capacity().trailing_zeros()..).pop()
+        key.slice(self.shift..).pop()
+    }
+
+    /// Loads the [`Transposition`] stored at `idx` along with the part of its key that is known.
+    #[inline(always)]
+    fn load(&self, idx: usize) -> Option<(Zobrist, Transposition)> {
+        let bits = Bits::new(self.cache.get(idx).assume().load(Relaxed));
+        let SignedTransposition(sig, t) = Option::decode(bits)?;
+        let key = Zobrist::new(idx as u64 | sig.cast::<u64>().checked_shl(self.shift).unwrap_or(0));
+        Some((key, Binary::decode(t)))
     }
 }
 
@@ -410,6 +505,74 @@ mod tests {
         assert_eq!(tt.get(k), Some(t));
     }
 
+    #[proptest]
+    fn clear_discards_all_transpositions(
+        #[by_ref] mut tt: TranspositionTable,
+        t: Transposition,
+        k: Zobrist,
+        n: ThreadCount,
+    ) {
+        tt.set(k, t);
+        tt.clear(n);
+        assert!(tt.cache.iter().all(|e| e.load(Relaxed) == 0));
+    }
+
+    #[proptest]
+    fn resize_preserves_size_limit(mut tt: TranspositionTable, s: HashSize, n: ThreadCount) {
+        tt.resize(s, n);
+        assert!(tt.size() <= s);
+    }
+
+    #[proptest]
+    fn resize_rehashes_transpositions_when_growing(
+        #[strategy(..HashSize::MAX.trailing_zeros())] bits: u32,
+        #[strategy(#bits..=HashSize::MAX.trailing_zeros())] grow: u32,
+        t: Transposition,
+        k: Zobrist,
+        n: ThreadCount,
+    ) {
+        let mut tt = TranspositionTable::new(HashSize::new(1 << bits));
+        prop_assume!(tt.capacity() > 0);
+
+        tt.set(k, t);
+        tt.resize(HashSize::new(1 << grow), n);
+        assert_eq!(tt.get(k), Some(t));
+    }
+
+    #[proptest]
+    fn resize_rehashes_transpositions_when_shrinking(
+        #[strategy(..=HashSize::MAX.trailing_zeros())] bits: u32,
+        #[strategy(..=#bits)] shrink: u32,
+        t: Transposition,
+        k: Zobrist,
+        n: ThreadCount,
+    ) {
+        let mut tt = TranspositionTable::new(HashSize::new(1 << bits));
+        tt.set(k, t);
+        tt.resize(HashSize::new(1 << shrink), n);
+        prop_assume!(tt.capacity() > 0);
+        assert_eq!(tt.get(k), Some(t));
+    }
+
+    #[proptest]
+    fn resize_prefers_deeper_transpositions_when_shrinking(
+        #[strategy(1..=HashSize::MAX.trailing_zeros())] bits: u32,
+        t: Transposition,
+        #[filter(#u.draft() > #t.draft())] u: Transposition,
+        k: Zobrist,
+        n: ThreadCount,
+    ) {
+        let mut tt = TranspositionTable::new(HashSize::new(1 << bits));
+        prop_assume!(tt.capacity() > 1);
+
+        let l = k ^ Zobrist::new(tt.capacity() as u64 >> 1);
+        tt.set(k, t);
+        tt.set(l, u);
+        tt.resize(HashSize::new(1 << (bits - 1)), n);
+        assert_eq!(tt.get(l), Some(u));
+        assert_eq!(tt.get(k), None);
+    }
+
     #[proptest]
     fn set_does_nothing_if_capacity_is_zero(k: Zobrist, t: Transposition) {
         TranspositionTable::new(HashSize::new(0)).set(k, t);
@@ -0,0 +1,315 @@
+use crate::chess::Position;
+use crate::search::Score;
+use crate::util::Integer;
+use std::{cmp::Ordering, ops::RangeInclusive};
+
+/// A position reached in a game, along with the final result of that game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct WdlSample {
+    /// The score from the point of view of the side to move.
+    pub score: Score,
+    /// The material on the board, see [`WdlModel::material`].
+    pub material: u8,
+    /// The result from the point of view of the side to move.
+    pub result: Ordering,
+}
+
+/// A [win-draw-loss model] of the expected result of a game given its [`Score`].
+///
+/// The win rate is a logistic function of the score, parameterized by its midpoint `a` and
+/// its scale `b`, which are themselves cubic polynomials of the material on the board.
+///
+/// [win-draw-loss model]: https://www.chessprogramming.org/Pawn_Advantage,_Win_Percentage,_and_Elo
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WdlModel {
+    a: [f64; 4],
+    b: [f64; 4],
+}
+
+impl Default for WdlModel {
+    /// The model fitted to 800 games of self-play at 1000 nodes per move from seed 7, i.e.
+    /// `cargo run --release --features wdl --bin wdl -- 800 1000 7`.
+    fn default() -> Self {
+        WdlModel::new(
+            [-1355.13, 3571.81, -2889.79, 901.11],
+            [-754.62, 1920.29, -1467.74, 431.42],
+        )
+    }
+}
+
+impl WdlModel {
+    /// The range of material the model distinguishes.
+    const MATERIAL: RangeInclusive<u8> = 17..=78;
+
+    /// Constructs a model from the coefficients of `a` and `b`, highest degree first.
+    pub fn new(a: [f64; 4], b: [f64; 4]) -> Self {
+        WdlModel { a, b }
+    }
+
+    /// The coefficients of `a`, highest degree first.
+    pub fn a(&self) -> [f64; 4] {
+        self.a
+    }
+
+    /// The coefficients of `b`, highest degree first.
+    pub fn b(&self) -> [f64; 4] {
+        self.b
+    }
+
+    /// The material on the board, counting pawns as 1, minor pieces as 3, rooks as 5, and queens as 9.
+    pub fn material(pos: &Position) -> u8 {
+        const VALUES: [u8; 6] = [1, 3, 3, 5, 9, 0];
+        pos.iter().map(|(p, _)| VALUES[p.role() as usize]).sum()
+    }
+
+    /// The variable of the polynomials given the material on the board.
+    fn x(material: u8) -> f64 {
+        let (lo, hi) = Self::MATERIAL.into_inner();
+        material.clamp(lo, hi) as f64 / 58.
+    }
+
+    /// Evaluates a polynomial at `x`.
+    fn polynomial(coefficients: [f64; 4], x: f64) -> f64 {
+        coefficients.iter().fold(0., |p, &c| p * x + c)
+    }
+
+    /// The midpoint and scale of the logistic function given the material on the board.
+    fn parameters(&self, material: u8) -> (f64, f64) {
+        let x = Self::x(material);
+        let a = Self::polynomial(self.a, x);
+        let b = Self::polynomial(self.b, x);
+        (a, b)
+    }
+
+    /// The logistic function with midpoint `a` and scale `b`.
+    fn logistic(s: f64, a: f64, b: f64) -> f64 {
+        1. / (1. + ((a - s) / b).exp())
+    }
+
+    /// The expected win rate of the side to move.
+    pub fn win_rate(&self, score: Score, material: u8) -> f64 {
+        match score.mate() {
+            Some(_) if score.get() > 0 => 1.,
+            Some(_) => 0.,
+            None => {
+                let (a, b) = self.parameters(material);
+                Self::logistic(score.get() as f64, a, b)
+            }
+        }
+    }
+
+    /// The expected wins, draws, and losses of the side to move per mille.
+    pub fn wdl(&self, score: Score, material: u8) -> (u32, u32, u32) {
+        let l = (self.win_rate(-score, material) * 1000.).round() as u32;
+        let w = ((self.win_rate(score, material) * 1000.).round() as u32).min(1000 - l);
+        (w, 1000 - w - l, l)
+    }
+
+    /// Converts a score to centipawns, such that an advantage of 100 means a 50% win rate.
+    pub fn normalize(&self, score: Score, material: u8) -> i32 {
+        let (a, _) = self.parameters(material);
+        (score.get() as f64 * 100. / a).round() as i32
+    }
+
+    /// Fits the model to the results of a set of games.
+    ///
+    /// Returns [`None`] if the samples cover too few different amounts of material.
+    pub fn fit(samples: &[WdlSample]) -> Option<Self> {
+        let (lo, hi) = Self::MATERIAL.into_inner();
+        let mut buckets = vec![Vec::new(); (hi - lo + 1) as usize];
+        for s in samples.iter().filter(|s| s.score.mate().is_none()) {
+            let m = s.material.clamp(lo, hi);
+            let result = match s.result {
+                Ordering::Greater => 0,
+                Ordering::Equal => 1,
+                Ordering::Less => 2,
+            };
+
+            buckets[(m - lo) as usize].push((s.score.get() as f64, result));
+        }
+
+        // The parameters are first fitted for every amount of material individually.
+        let points = buckets.iter().zip(lo..=hi).filter(|(b, _)| !b.is_empty());
+        let points = Vec::from_iter(points.map(|(b, m)| {
+            let (a, s) = Self::fit_logistic(b);
+            (Self::x(m), a, s, b.len() as f64)
+        }));
+
+        let a = Self::fit_polynomial(points.iter().map(|&(x, a, _, w)| (x, a, w)))?;
+        let b = Self::fit_polynomial(points.iter().map(|&(x, _, b, w)| (x, b, w)))?;
+        Some(WdlModel::new(a, b))
+    }
+
+    /// The average negative log-likelihood of the results given the midpoint `a` and the scale `b`.
+    fn loss(results: &[(f64, usize)], a: f64, b: f64) -> f64 {
+        let likelihoods = results.iter().map(|&(s, r)| {
+            let w = Self::logistic(s, a, b);
+            let l = Self::logistic(-s, a, b);
+            [w, 1. - w - l, l][r]
+        });
+
+        -likelihoods.map(|p| p.max(1e-9).ln()).sum::<f64>() / results.len() as f64
+    }
+
+    /// Finds the midpoint and scale of the logistic function that best predict the results.
+    fn fit_logistic(results: &[(f64, usize)]) -> (f64, f64) {
+        // The parameters are optimized in pawns by gradient descent with an adaptive step.
+        let loss = |[a, b]: [f64; 2]| Self::loss(results, a * 100., b * 100.);
+        let (mut params, mut step) = ([1., 1.], 0.1);
+        let mut current = loss(params);
+
+        for _ in 0..1000 {
+            const H: f64 = 1e-6;
+            let da =
+                (loss([params[0] + H, params[1]]) - loss([params[0] - H, params[1]])) / (2. * H);
+            let db =
+                (loss([params[0], params[1] + H]) - loss([params[0], params[1] - H])) / (2. * H);
+            let next = [params[0] - step * da, (params[1] - step * db).max(0.01)];
+            let candidate = loss(next);
+
+            if candidate < current {
+                (params, current, step) = (next, candidate, step * 1.2);
+            } else if step > 1e-9 {
+                step /= 2.;
+            } else {
+                break;
+            }
+        }
+
+        (params[0] * 100., params[1] * 100.)
+    }
+
+    /// Finds the cubic polynomial that best fits the weighted points by least squares.
+    fn fit_polynomial(points: impl Iterator<Item = (f64, f64, f64)>) -> Option<[f64; 4]> {
+        // The normal equations, augmented with the right-hand side.
+        let mut m = [[0f64; 5]; 4];
+        for (x, y, w) in points {
+            let powers = [x.powi(3), x.powi(2), x, 1.];
+            for i in 0..4 {
+                for j in 0..4 {
+                    m[i][j] += w * powers[i] * powers[j];
+                }
+
+                m[i][4] += w * powers[i] * y;
+            }
+        }
+
+        // Gaussian elimination with partial pivoting.
+        for i in 0..4 {
+            let pivot = (i..4).max_by(|&r, &s| m[r][i].abs().total_cmp(&m[s][i].abs()))?;
+            if m[pivot][i].abs() < 1e-12 {
+                return None;
+            }
+
+            m.swap(i, pivot);
+            for r in 0..4 {
+                if r != i {
+                    let f = m[r][i] / m[i][i];
+                    for c in i..5 {
+                        m[r][c] -= f * m[i][c];
+                    }
+                }
+            }
+        }
+
+        Some([0, 1, 2, 3].map(|i| m[i][4] / m[i][i]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[test]
+    fn material_of_initial_position_is_78() {
+        assert_eq!(WdlModel::material(&Position::default()), 78);
+    }
+
+    #[proptest]
+    fn wdl_adds_up_to_one_thousand(s: Score, m: u8) {
+        let (w, d, l) = WdlModel::default().wdl(s, m);
+        assert_eq!(w + d + l, 1000);
+    }
+
+    #[proptest]
+    fn wdl_is_antisymmetric(s: Score, m: u8) {
+        let (w, d, l) = WdlModel::default().wdl(s, m);
+        assert_eq!(WdlModel::default().wdl(-s, m), (l, d, w));
+    }
+
+    #[proptest]
+    fn wdl_is_certain_for_mate_scores(#[filter(#s.mate().is_some())] s: Score, m: u8) {
+        let wdl = WdlModel::default().wdl(s, m);
+        assert!(wdl == (1000, 0, 0) || wdl == (0, 0, 1000));
+    }
+
+    #[proptest]
+    fn win_rate_increases_with_score(
+        #[filter(#s.mate().is_none())] s: Score,
+        #[filter(#t.mate().is_none() && #s < #t)] t: Score,
+        m: u8,
+    ) {
+        let model = WdlModel::default();
+        assert!(model.win_rate(s, m) <= model.win_rate(t, m));
+    }
+
+    #[proptest]
+    fn normalized_advantage_of_100_means_even_odds_of_winning(#[strategy(17u8..=78)] m: u8) {
+        let model = WdlModel::default();
+        let (a, _) = model.parameters(m);
+        let s: Score = (a.round() as i32).saturate();
+        assert!((model.normalize(s, m) - 100).abs() <= 1);
+        assert!((model.win_rate(s, m) - 0.5).abs() < 0.01);
+    }
+
+    #[proptest]
+    fn normalize_preserves_sign(#[filter(#s.mate().is_none())] s: Score, m: u8) {
+        let cp = WdlModel::default().normalize(s, m);
+        assert!(cp == 0 || cp.signum() == s.get().signum() as i32);
+    }
+
+    #[test]
+    fn fit_recovers_model_that_generated_the_samples() {
+        let model = WdlModel::default();
+
+        let mut samples = Vec::new();
+        for m in (20..=70).step_by(10) {
+            for s in (-600..=600).step_by(50) {
+                let (w, d, l) = model.wdl(s.saturate(), m);
+                for (n, result) in [
+                    (w, Ordering::Greater),
+                    (d, Ordering::Equal),
+                    (l, Ordering::Less),
+                ] {
+                    let sample = WdlSample {
+                        score: s.saturate(),
+                        material: m,
+                        result,
+                    };
+
+                    samples.extend((0..n / 10).map(|_| sample));
+                }
+            }
+        }
+
+        let fitted = WdlModel::fit(&samples).unwrap();
+        for m in (20..=70).step_by(10) {
+            let (a, b) = model.parameters(m);
+            let (c, d) = fitted.parameters(m);
+            assert!((a - c).abs() < 0.05 * a, "{a} {c}");
+            assert!((b - d).abs() < 0.05 * b, "{b} {d}");
+        }
+    }
+
+    #[test]
+    fn fit_fails_without_enough_samples() {
+        let sample = WdlSample {
+            score: Score::new(0),
+            material: 78,
+            result: Ordering::Equal,
+        };
+
+        assert_eq!(WdlModel::fit(&[sample; 10]), None);
+    }
+}
@@ -0,0 +1,7 @@
+mod arena;
+#[cfg(feature = "spsa")]
+mod spsa;
+
+pub use arena::*;
+#[cfg(feature = "spsa")]
+pub use spsa::*;
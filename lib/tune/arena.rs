@@ -0,0 +1,160 @@
+use crate::chess::Color;
+use crate::nnue::Evaluator;
+use crate::search::{Engine, Limits, WdlModel, WdlSample};
+use rand::prelude::*;
+use std::cmp::Ordering;
+
+/// Plays games between engines within the same process.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Arena {
+    limits: Limits,
+    plies: usize,
+}
+
+impl Arena {
+    /// Constructs an arena where every move is searched within `limits`.
+    ///
+    /// Games that last longer than `plies` are adjudicated as draws.
+    pub fn new(limits: Limits, plies: usize) -> Self {
+        Arena { limits, plies }
+    }
+
+    /// Generates an opening by playing `plies` random moves from the initial position.
+    pub fn opening<R: Rng + ?Sized>(&self, rng: &mut R, plies: usize) -> Evaluator {
+        'opening: loop {
+            let mut pos = Evaluator::default();
+            for _ in 0..plies {
+                match pos.moves().flatten().choose(rng) {
+                    None => continue 'opening,
+                    Some(m) => pos.play(m),
+                }
+            }
+
+            if pos.outcome().is_none() {
+                break pos;
+            }
+        }
+    }
+
+    /// Plays a game from `opening`, returning the winning side, if any.
+    ///
+    /// Both engines forget everything they learned before the game starts.
+    pub fn play(
+        &self,
+        white: &mut Engine,
+        black: &mut Engine,
+        opening: &Evaluator,
+    ) -> Option<Color> {
+        white.clear();
+        black.clear();
+
+        let mut pos = opening.clone();
+        for _ in 0..self.plies {
+            if let Some(o) = pos.outcome() {
+                return o.winner();
+            }
+
+            let engine = match pos.turn() {
+                Color::White => &*white,
+                Color::Black => &*black,
+            };
+
+            match engine.search(&pos, &self.limits).head() {
+                None => break,
+                Some(m) => pos.play(m),
+            }
+        }
+
+        pos.outcome().and_then(|o| o.winner())
+    }
+
+    /// Plays a pair of games from `opening` with colors reversed.
+    ///
+    /// Returns the number of games won by `a` minus the number of games won by `b`.
+    pub fn pair(&self, a: &mut Engine, b: &mut Engine, opening: &Evaluator) -> i32 {
+        let first = match self.play(a, b, opening) {
+            Some(Color::White) => 1,
+            Some(Color::Black) => -1,
+            None => 0,
+        };
+
+        let second = match self.play(b, a, opening) {
+            Some(Color::White) => -1,
+            Some(Color::Black) => 1,
+            None => 0,
+        };
+
+        first + second
+    }
+
+    /// Plays a game from `opening` where the engine plays against itself.
+    ///
+    /// Returns every position searched, along with its score and the result of the game.
+    pub fn record(&self, engine: &mut Engine, opening: &Evaluator) -> Vec<WdlSample> {
+        engine.clear();
+
+        let mut pos = opening.clone();
+        let mut positions = Vec::new();
+        for _ in 0..self.plies {
+            if pos.outcome().is_some() {
+                break;
+            }
+
+            let pv = engine.search(&pos, &self.limits);
+            let Some(m) = pv.head() else {
+                break;
+            };
+
+            positions.push((pos.turn(), pv.score(), WdlModel::material(&pos)));
+            pos.play(m);
+        }
+
+        let winner = pos.outcome().and_then(|o| o.winner());
+        let samples = positions.into_iter().map(|(turn, score, material)| {
+            let result = match winner {
+                None => Ordering::Equal,
+                Some(c) if c == turn => Ordering::Greater,
+                Some(_) => Ordering::Less,
+            };
+
+            WdlSample {
+                score,
+                material,
+                result,
+            }
+        });
+
+        samples.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_pcg::Pcg64;
+    use test_strategy::proptest;
+
+    #[proptest(cases = 1)]
+    fn opening_is_not_over(seed: u64, #[strategy(..16usize)] plies: usize) {
+        let arena = Arena::new(Limits::Nodes(1), 1);
+        let opening = arena.opening(&mut Pcg64::seed_from_u64(seed), plies);
+        assert_eq!(opening.outcome(), None);
+    }
+
+    #[proptest(cases = 1)]
+    fn engines_that_are_alike_tie_pair_of_games(seed: u64) {
+        let arena = Arena::new(Limits::Nodes(300), 16);
+        let opening = arena.opening(&mut Pcg64::seed_from_u64(seed), 8);
+        let (mut a, mut b) = (Engine::new(), Engine::new());
+        assert_eq!(arena.pair(&mut a, &mut b, &opening), 0);
+    }
+
+    #[proptest(cases = 1)]
+    fn recorded_game_samples_every_position_searched(seed: u64) {
+        let arena = Arena::new(Limits::Nodes(300), 16);
+        let opening = arena.opening(&mut Pcg64::seed_from_u64(seed), 8);
+        let samples = arena.record(&mut Engine::new(), &opening);
+        assert!(!samples.is_empty() && samples.len() <= 16);
+        assert_eq!(samples[0].material, WdlModel::material(&opening));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::search::Params;
+use rand::prelude::*;
+
+/// An implementation of [SPSA] over the tunable search [`Params`].
+///
+/// [SPSA]: https://www.chessprogramming.org/SPSA
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spsa {
+    theta: Vec<f64>,
+    rate: f64,
+    stability: f64,
+    iterations: u32,
+}
+
+impl Spsa {
+    const ALPHA: f64 = 0.602;
+    const GAMMA: f64 = 0.101;
+
+    /// Starts tuning from the compiled defaults.
+    ///
+    /// The `rate` scales how far parameters move on every iteration,
+    /// while the `stability` slows down the decay of the rate over the first iterations.
+    pub fn new(rate: f64, stability: f64) -> Self {
+        Spsa {
+            theta: Params::LIST.iter().map(|p| p.default.into()).collect(),
+            rate,
+            stability,
+            iterations: 0,
+        }
+    }
+
+    /// The number of iterations performed so far.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// The current estimate of the best parameters.
+    pub fn params(&self) -> Params {
+        self.round(|_, t| t)
+    }
+
+    /// Rounds each parameter mapped by `f` to the nearest valid value.
+    fn round(&self, mut f: impl FnMut(usize, f64) -> f64) -> Params {
+        let mut params = Params::default();
+        for (i, (p, &t)) in Params::LIST.iter().zip(&self.theta).enumerate() {
+            params.set(p.name, f(i, t).round() as i16);
+        }
+
+        params
+    }
+
+    /// Performs one iteration by evaluating a random perturbation of the current parameters.
+    ///
+    /// The closure measures how much the first set of parameters outperforms the second,
+    /// in the range `-1.0..=1.0`.
+    pub fn step<R: Rng + ?Sized>(&mut self, rng: &mut R, eval: impl FnOnce(Params, Params) -> f64) {
+        let k = self.iterations as f64 + 1.;
+        let a = self.rate / (k + self.stability).powf(Self::ALPHA);
+        let c = 1. / k.powf(Self::GAMMA);
+
+        // Each parameter is perturbed by its own step, in a random direction.
+        let delta: Vec<f64> = Params::LIST
+            .iter()
+            .map(|p| match rng.gen() {
+                true => c * p.step as f64,
+                false => -c * p.step as f64,
+            })
+            .collect();
+
+        let plus = self.round(|i, t| t + delta[i]);
+        let minus = self.round(|i, t| t - delta[i]);
+        let result = eval(plus, minus).clamp(-1., 1.);
+
+        for ((p, t), d) in Params::LIST.iter().zip(&mut self.theta).zip(delta) {
+            *t = (*t + a * result * d).clamp(p.min.into(), p.max.into());
+        }
+
+        self.iterations += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_pcg::Pcg64;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn starts_from_compiled_defaults(
+        #[strategy(0f64..10.)] r: f64,
+        #[strategy(0f64..100.)] s: f64,
+    ) {
+        let spsa = Spsa::new(r, s);
+        assert_eq!(spsa.params(), Params::default());
+        assert_eq!(spsa.iterations(), 0);
+    }
+
+    #[proptest]
+    fn step_keeps_parameters_within_range(
+        seed: u64,
+        #[strategy(0f64..100.)] r: f64,
+        #[strategy(-2f64..2.)] x: f64,
+    ) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut spsa = Spsa::new(r, 0.);
+        spsa.step(&mut rng, |_, _| x);
+
+        let params = spsa.params();
+        for p in Params::LIST {
+            assert!((p.min..=p.max).contains(&params.get(p.name).unwrap()));
+        }
+
+        assert_eq!(spsa.iterations(), 1);
+    }
+
+    #[proptest]
+    fn step_moves_parameters_towards_better_perturbation(seed: u64) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut spsa = Spsa::new(10., 0.);
+        let p = Params::LIST[0];
+
+        spsa.step(&mut rng, |plus, minus| {
+            let (a, b) = (plus.get(p.name), minus.get(p.name));
+            (a.cmp(&b) as i8).into()
+        });
+
+        assert!(spsa.params().get(p.name) >= Some(p.default));
+    }
+}
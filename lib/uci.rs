@@ -1,48 +1,40 @@
-use crate::chess::{Color, Move, Perspective};
+use crate::chess::{Color, Perspective};
 use crate::nnue::Evaluator;
-use crate::search::{Engine, HashSize, Limits, Options, ThreadCount};
-use crate::util::{Assume, Integer, Trigger};
-use futures::channel::oneshot::channel as oneshot;
-use futures::{future::FusedFuture, prelude::*, select_biased as select, stream::FusedStream};
+use crate::search::{Depth, Engine, HashSize, Limits, Options, Pv, ThreadCount, WdlModel};
+use crate::util::Integer;
+use futures::{prelude::*, select_biased as select, stream::FusedStream};
+use std::fmt::{Debug, Display};
 use std::time::{Duration, Instant};
-use std::{fmt::Debug, io::Write, mem::transmute, str, thread};
+
+#[cfg(feature = "spsa")]
+use crate::search::{Param, Params};
+
+#[cfg(feature = "trace")]
+use crate::search::{Ply, Trace};
 
 #[cfg(test)]
 use proptest::prelude::*;
 
-/// Runs the provided closure on a thread where blocking is acceptable.
-///
-/// # Safety
-///
-/// Must be awaited on through completion strictly before any
-/// of the variables `f` may capture is dropped.
-#[must_use]
-unsafe fn unblock<F, R>(f: F) -> impl FusedFuture<Output = R>
-where
-    F: FnOnce() -> R + Send,
-    R: Send,
-{
-    let (tx, rx) = oneshot();
-    thread::spawn(transmute::<
-        Box<dyn FnOnce() + Send>,
-        Box<dyn FnOnce() + Send + 'static>,
-    >(Box::new(move || tx.send(f()).assume()) as _));
-    rx.map(Assume::assume)
-}
+mod command;
+mod r#move;
+mod option;
+mod response;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-struct UciMove(Move);
+pub use command::*;
+pub use option::*;
+pub use r#move::*;
+pub use response::*;
 
-impl PartialEq<str> for UciMove {
-    fn eq(&self, other: &str) -> bool {
-        let mut buffer = [b'\0'; 5];
-        write!(&mut buffer[..], "{}", self.0).assume();
-        let len = if buffer[4] == b'\0' { 4 } else { 5 };
-        other == unsafe { str::from_utf8_unchecked(&buffer[..len]) }
-    }
+/// Generates durations with millisecond precision.
+#[cfg(test)]
+fn millis() -> impl Strategy<Value = std::time::Duration> {
+    (0..1u64 << 32).prop_map(std::time::Duration::from_millis)
 }
 
 /// A basic UCI server.
+///
+/// Reads [`UciCommand`]s, or anything that can be converted into them, such as lines of text,
+/// from the input stream and writes [`UciResponse`]s to the output sink.
 #[derive(Debug, Default)]
 #[cfg_attr(test, derive(test_strategy::Arbitrary))]
 #[cfg_attr(test, arbitrary(args = I,
@@ -55,9 +47,149 @@ pub struct Uci<I, O> {
     engine: Engine,
     options: Options,
     position: Evaluator,
+    show_wdl: bool,
+    #[cfg(feature = "trace")]
+    #[cfg_attr(test, strategy(Just(TraceOptions::default())))]
+    trace: TraceOptions,
 }
 
+/// Where to record the search tree and how much of it.
+#[cfg(feature = "trace")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+struct TraceOptions {
+    file: String,
+    plies: Ply,
+    nodes: u64,
+}
+
+#[cfg(feature = "trace")]
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            file: String::new(),
+            plies: Ply::new(4),
+            nodes: 100_000,
+        }
+    }
+}
+
+/// Applies a parsed value to the corresponding option.
+type UciOptionSetter<I, O> = fn(&mut Uci<I, O>, UciOptionValue);
+
 impl<I, O> Uci<I, O> {
+    /// The options supported by this server.
+    fn options() -> [(UciOption, UciOptionSetter<I, O>); 6] {
+        [
+            (
+                UciOption::spin(
+                    "Hash",
+                    (HashSize::default().get() >> 20) as i64,
+                    (HashSize::MIN >> 20) as i64,
+                    (HashSize::MAX >> 20) as i64,
+                ),
+                |uci, value| {
+                    if let UciOptionValue::Spin(mb) = value {
+                        let hash = HashSize::new((mb as usize) << 20);
+                        uci.options.hash = hash;
+                        uci.engine.set_hash(hash);
+                    }
+                },
+            ),
+            (
+                UciOption::spin(
+                    "Threads",
+                    ThreadCount::default().get() as i64,
+                    ThreadCount::MIN as i64,
+                    ThreadCount::MAX as i64,
+                ),
+                |uci, value| {
+                    if let UciOptionValue::Spin(n) = value {
+                        let threads = ThreadCount::new(n as usize);
+                        uci.options.threads = threads;
+                        uci.engine.set_threads(threads);
+                    }
+                },
+            ),
+            (
+                UciOption::spin(
+                    "Move Overhead",
+                    Options::default().overhead.as_millis() as i64,
+                    0,
+                    5000,
+                ),
+                |uci, value| {
+                    if let UciOptionValue::Spin(ms) = value {
+                        let overhead = Duration::from_millis(ms as u64);
+                        uci.options.overhead = overhead;
+                        uci.engine.set_overhead(overhead);
+                    }
+                },
+            ),
+            (
+                UciOption::check("Deterministic", Options::default().deterministic),
+                |uci, value| {
+                    if let UciOptionValue::Check(deterministic) = value {
+                        uci.options.deterministic = deterministic;
+                        uci.engine.set_deterministic(deterministic);
+                    }
+                },
+            ),
+            (UciOption::check("UCI_ShowWDL", false), |uci, value| {
+                if let UciOptionValue::Check(show) = value {
+                    uci.show_wdl = show;
+                }
+            }),
+            (UciOption::button("Clear Hash"), |uci, _| uci.engine.clear()),
+        ]
+    }
+
+    /// The options that control tracing of the search tree.
+    #[cfg(feature = "trace")]
+    fn trace_options() -> [(UciOption, UciOptionSetter<I, O>); 3] {
+        [
+            (UciOption::string("Trace File", ""), |uci, value| {
+                if let UciOptionValue::String(file) = value {
+                    uci.trace.file = file;
+                }
+            }),
+            (
+                UciOption::spin(
+                    "Trace Plies",
+                    TraceOptions::default().plies.cast(),
+                    0,
+                    Ply::MAX as i64,
+                ),
+                |uci, value| {
+                    if let UciOptionValue::Spin(n) = value {
+                        uci.trace.plies = n.saturate();
+                    }
+                },
+            ),
+            (
+                UciOption::spin(
+                    "Trace Nodes",
+                    TraceOptions::default().nodes as i64,
+                    0,
+                    i64::MAX,
+                ),
+                |uci, value| {
+                    if let UciOptionValue::Spin(n) = value {
+                        uci.trace.nodes = n as u64;
+                    }
+                },
+            ),
+        ]
+    }
+
+    /// The tunable search parameters, exposed as additional options.
+    #[cfg(feature = "spsa")]
+    fn params() -> impl Iterator<Item = (UciOption, &'static Param)> {
+        Params::LIST.iter().map(|p| {
+            let option = UciOption::spin(p.name, p.default.into(), p.min.into(), p.max.into());
+            (option, p)
+        })
+    }
+
     /// Constructs a new uci server instance.
     pub fn new(input: I, output: O) -> Self {
         Self {
@@ -66,135 +198,369 @@ impl<I, O> Uci<I, O> {
             engine: Engine::default(),
             options: Options::default(),
             position: Evaluator::default(),
+            show_wdl: false,
+            #[cfg(feature = "trace")]
+            trace: TraceOptions::default(),
+        }
+    }
+
+    /// Reports the score and moves of a principal variation.
+    ///
+    /// Scores are reported in centipawns normalized by the [`WdlModel`].
+    fn pv(&self, pv: &Pv) -> UciInfo {
+        let model = WdlModel::default();
+        let material = WdlModel::material(&self.position);
+        let score = match pv.score().mate() {
+            Some(p) if p > 0 => UciScore::Mate((p.cast::<i32>() + 1) / 2),
+            Some(p) => UciScore::Mate((p.cast::<i32>() - 1) / 2),
+            None => UciScore::Cp(model.normalize(pv.score(), material)),
+        };
+
+        UciInfo {
+            score: Some(score),
+            wdl: self.show_wdl.then(|| model.wdl(pv.score(), material)),
+            pv: pv.moves().iter().map(UciMove::from).collect(),
+            ..UciInfo::default()
+        }
+    }
+
+    /// The search limits requested by `go`.
+    fn limits(&self, go: &UciGo) -> Limits {
+        let (clock, inc) = match self.position.turn() {
+            Color::White => (go.wtime, go.winc),
+            Color::Black => (go.btime, go.binc),
+        };
+
+        if !go.searchmoves.is_empty() {
+            eprintln!("ignored unsupported argument `searchmoves`");
+        }
+
+        if go.ponder {
+            eprintln!("ignored unsupported argument `ponder`");
+        }
+
+        if let Some(d) = go.depth {
+            Limits::Depth(d.saturate())
+        } else if let Some(n) = go.nodes {
+            Limits::Nodes(n)
+        } else if let Some(t) = go.movetime {
+            Limits::Time(t)
+        } else if let Some(t) = clock {
+            Limits::Clock(t, inc.unwrap_or_default(), go.movestogo)
+        } else if let Some(n) = go.mate {
+            Limits::Depth(n.saturating_mul(2).saturate())
+        } else {
+            Limits::None
+        }
+    }
+
+    /// What is left of `limits` after spending `time` and `nodes` looking for a forced mate.
+    ///
+    /// If the mate solver was stopped, only a shallow search is left to find some move to play.
+    fn remaining(limits: &Limits, time: Duration, nodes: u64, stopped: bool) -> Limits {
+        match limits {
+            _ if stopped => Limits::Depth(Depth::new(1)),
+            Limits::Nodes(n) => Limits::Nodes(n.saturating_sub(nodes)),
+            Limits::Time(t) => Limits::Time(t.saturating_sub(time)),
+            Limits::Clock(t, i, m) => Limits::Clock(t.saturating_sub(time), *i, *m),
+            limits => limits.clone(),
         }
     }
 }
 
-impl<I: FusedStream<Item = String> + Unpin, O: Sink<String> + Unpin> Uci<I, O> {
-    async fn go(&mut self, limits: &Limits) -> Result<(), O::Error> {
-        let stopper = Trigger::armed();
+impl<I, O> Uci<I, O>
+where
+    I: FusedStream<Item: TryInto<UciCommand, Error: Display>> + Unpin,
+    O: Sink<UciResponse> + Unpin,
+{
+    async fn info(&mut self, info: UciInfo) -> Result<(), O::Error> {
+        self.output.send(UciResponse::Info(Box::new(info))).await
+    }
+
+    async fn report(&mut self, string: String) -> Result<(), O::Error> {
+        let string = Some(string);
+        self.info(UciInfo {
+            string,
+            ..UciInfo::default()
+        })
+        .await
+    }
+
+    #[cfg(feature = "spsa")]
+    async fn set_param(
+        &mut self,
+        option: UciOption,
+        param: &Param,
+        value: Option<&str>,
+    ) -> Result<(), O::Error> {
+        match option.parse(value) {
+            Ok(UciOptionValue::Spin(v)) => {
+                self.options.params.set(param.name, v as i16);
+                self.engine.set_params(self.options.params);
+            }
+
+            Ok(_) => {}
+
+            Err(e) => {
+                let name = option.name();
+                let info = format!("invalid value for option `{name}`, {e}");
+                self.report(info).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts recording the search tree, if a trace file is set.
+    #[cfg(feature = "trace")]
+    async fn trace(&mut self) -> Result<(), O::Error> {
+        let TraceOptions { file, plies, nodes } = self.trace.clone();
+        let trace = match file.as_str() {
+            "" => None,
+            path => match Trace::create(path, plies, nodes) {
+                Ok(trace) => Some(trace),
+                Err(e) => {
+                    self.report(format!("failed to create trace file `{path}`, {e}"))
+                        .await?;
+
+                    None
+                }
+            },
+        };
+
+        self.engine.set_trace(trace);
+        Ok(())
+    }
+
+    async fn set_option(&mut self, name: &str, value: Option<&str>) -> Result<(), O::Error> {
+        #[cfg(feature = "spsa")]
+        if let Some((option, param)) = Self::params().find(|(o, _)| o.is(name)) {
+            return self.set_param(option, param, value).await;
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let mut options = Self::options().into_iter();
+
+        #[cfg(feature = "trace")]
+        let mut options = Self::options().into_iter().chain(Self::trace_options());
 
-        let mut search =
-            unsafe { unblock(|| self.engine.search(&self.position, limits, &stopper)) };
+        let Some((option, set)) = options.find(|(o, _)| o.is(name)) else {
+            return self.report(format!("unknown option `{name}`")).await;
+        };
+
+        match option.parse(value) {
+            Ok(v) => set(self, v),
+            Err(e) => {
+                let name = option.name();
+                let info = format!("invalid value for option `{name}`, {e}");
+                self.report(info).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Searches the current position within `limits`.
+    ///
+    /// Returns whether the server should keep running, which is not the case if `quit` is received.
+    async fn go(&mut self, limits: &Limits) -> Result<bool, O::Error> {
+        let mut search = self.engine.start(&self.position, limits);
+        let mut progress = search.subscribe();
+        let mut quit = false;
 
         let pv = loop {
             select! {
+                p = progress.next() => {
+                    if let Some(p) = p {
+                        let info = UciInfo {
+                            depth: Some(p.depth().cast()),
+                            time: Some(p.time()),
+                            nodes: Some(p.nodes()),
+                            ..self.pv(p.pv())
+                        };
+
+                        self.info(info).await?;
+                    }
+                },
+
                 pv = search => break pv,
-                line = self.input.next() => {
-                    match line.as_deref().map(str::trim) {
+
+                cmd = self.input.next() => {
+                    match cmd.map(TryInto::try_into) {
                         None => break search.await,
-                        Some("stop") => { stopper.disarm(); },
-                        Some(cmd) => eprintln!("ignored unsupported command `{cmd}` during search"),
+                        Some(Ok(UciCommand::Stop)) => search.stop(),
+                        Some(Ok(UciCommand::Quit)) => {
+                            search.stop();
+                            quit = true;
+                        }
+
+                        Some(Ok(cmd)) => eprintln!("ignored unsupported command `{cmd}` during search"),
+                        Some(Err(e)) => eprintln!("{e}"),
                     }
                 }
             }
         };
 
-        let info = match pv.score().mate() {
-            Some(p) if p > 0 => format!("info score mate {} pv {}", (p + 1) / 2, pv.moves()),
-            Some(p) => format!("info score mate {} pv {}", (p - 1) / 2, pv.moves()),
-            None => format!("info score cp {:+} pv {}", pv.score(), pv.moves()),
-        };
+        if quit {
+            return Ok(false);
+        }
 
-        self.output.send(info).await?;
+        self.info(self.pv(&pv)).await?;
 
         if let Some(m) = pv.head() {
-            self.output.send(format!("bestmove {m}")).await?;
+            self.output
+                .send(UciResponse::BestMove(m.into(), None))
+                .await?;
         }
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// Looks for a forced mate in at most `moves` moves within `limits`,
+    /// falling back to a regular search with whatever is left of `limits` if none is found.
+    ///
+    /// Returns whether the server should keep running, which is not the case if `quit` is received.
+    async fn mate(&mut self, moves: u32, limits: &Limits) -> Result<bool, O::Error> {
+        let timer = Instant::now();
+        let mut solver = self.engine.solve(&self.position, moves, limits);
+        let (mut stopped, mut quit) = (false, false);
+
+        let (result, nodes) = loop {
+            select! {
+                result = solver => break result,
+
+                cmd = self.input.next() => {
+                    match cmd.map(TryInto::try_into) {
+                        None => break solver.await,
+                        Some(Ok(UciCommand::Stop)) => {
+                            solver.stop();
+                            stopped = true;
+                        }
+
+                        Some(Ok(UciCommand::Quit)) => {
+                            solver.stop();
+                            quit = true;
+                        }
+
+                        Some(Ok(cmd)) => eprintln!("ignored unsupported command `{cmd}` during search"),
+                        Some(Err(e)) => eprintln!("{e}"),
+                    }
+                }
+            }
+        };
+
+        let solution = match result {
+            Ok(Some(solution)) => solution,
+            _ if quit => return Ok(false),
+            result => {
+                let info = match result {
+                    Err(e) => format!("no mate in {moves} found, {e}"),
+                    _ => format!("no mate in {moves}"),
+                };
+
+                self.report(info).await?;
+                let limits = Self::remaining(limits, timer.elapsed(), nodes, stopped);
+                return self.go(&limits).await;
+            }
+        };
+
+        let n = solution.moves() as u32;
+        let info = UciInfo {
+            depth: Some(2 * n - 1),
+            time: Some(timer.elapsed()),
+            nodes: Some(nodes),
+            score: Some(UciScore::Mate(n as i32)),
+            pv: solution.line().into_iter().map(UciMove::from).collect(),
+            ..UciInfo::default()
+        };
+
+        self.info(info).await?;
+        self.report(format!("solution {solution}")).await?;
+        self.output
+            .send(UciResponse::BestMove(solution.attack.into(), None))
+            .await?;
+
+        Ok(!quit)
     }
 
     async fn bench(&mut self, limits: &Limits) -> Result<(), O::Error> {
-        let stopper = Trigger::armed();
         let timer = Instant::now();
-        self.engine.search(&self.position, limits, &stopper);
-        let millis = timer.elapsed().as_millis();
+        self.engine.search(&self.position, limits);
+        let elapsed = timer.elapsed();
 
         let info = match limits {
-            Limits::Depth(d) => format!("info time {millis} depth {d}"),
-            Limits::Nodes(nodes) => format!(
-                "info time {millis} nodes {nodes} nps {}",
-                *nodes as u128 * 1000 / millis
-            ),
+            Limits::Depth(d) => UciInfo {
+                time: Some(elapsed),
+                depth: Some(d.cast()),
+                ..UciInfo::default()
+            },
+
+            Limits::Nodes(nodes) => UciInfo {
+                time: Some(elapsed),
+                nodes: Some(*nodes),
+                nps: Some((*nodes as u128 * 1000 / elapsed.as_millis().max(1)) as u64),
+                ..UciInfo::default()
+            },
+
             _ => return Ok(()),
         };
 
-        self.output.send(info).await
+        self.info(info).await
     }
 
     /// Runs the UCI server.
     pub async fn run(&mut self) -> Result<(), O::Error> {
-        while let Some(line) = self.input.next().await {
-            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
-                ["quit"] => return Ok(()),
-                [] | ["stop"] => continue,
-
-                ["go", "wtime", wtime, "btime", btime, "winc", winc, "binc", binc]
-                | ["go", "wtime", wtime, "winc", winc, "btime", btime, "binc", binc] => {
-                    let (t, i) = match self.position.turn() {
-                        Color::White => (wtime, winc),
-                        Color::Black => (btime, binc),
-                    };
-
-                    match (t.parse(), i.parse()) {
-                        (Err(e), _) | (_, Err(e)) => eprintln!("{e}"),
-                        (Ok(t), Ok(i)) => {
-                            let t = Duration::from_millis(t);
-                            let i = Duration::from_millis(i);
-                            self.go(&Limits::Clock(t, i)).await?;
-                        }
-                    }
+        while let Some(cmd) = self.input.next().await {
+            let cmd = match cmd.try_into() {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    eprintln!("{e}");
+                    continue;
                 }
+            };
 
-                ["go", "movetime", time] => match time.parse() {
-                    Ok(ms) => self.go(&Duration::from_millis(ms).into()).await?,
-                    Err(e) => eprintln!("{e}"),
-                },
+            match cmd {
+                UciCommand::Quit => return Ok(()),
+                UciCommand::Stop | UciCommand::PonderHit => continue,
 
-                ["go", "depth", depth] => match depth.parse() {
-                    Ok(d) => self.go(&Limits::Depth(d)).await?,
-                    Err(e) => eprintln!("{e}"),
-                },
+                UciCommand::Go(go) => {
+                    #[cfg(feature = "trace")]
+                    self.trace().await?;
 
-                ["go", "nodes", nodes] => match nodes.parse() {
-                    Ok(n) => self.go(&Limits::Nodes(n)).await?,
-                    Err(e) => eprintln!("{e}"),
-                },
-
-                ["go"] | ["go", "infinite"] => self.go(&Limits::None).await?,
-
-                ["bench", "depth", depth] => match depth.parse() {
-                    Ok(d) => self.bench(&Limits::Depth(d)).await?,
-                    Err(e) => eprintln!("{e}"),
-                },
+                    let limits = self.limits(&go);
+                    let running = match go.mate {
+                        None => self.go(&limits).await?,
+                        Some(n) => self.mate(n, &limits).await?,
+                    };
 
-                ["bench", "nodes", nodes] => match nodes.parse() {
-                    Ok(n) => self.bench(&Limits::Nodes(n)).await?,
-                    Err(e) => eprintln!("{e}"),
-                },
+                    if !running {
+                        return Ok(());
+                    }
+                }
 
-                ["position", "fen", fen @ ..] => match fen.join(" ").parse() {
-                    Err(e) => eprintln!("{e}"),
-                    Ok(pos) => self.position = pos,
-                },
+                UciCommand::Bench(go) => {
+                    let limits = self.limits(&go);
+                    self.bench(&limits).await?
+                }
 
-                ["position", "startpos"] => self.position = Evaluator::default(),
-                ["position", "startpos", "moves", moves @ ..] => {
-                    self.position = Evaluator::default();
+                UciCommand::Position { fen, moves } => {
+                    self.position = match fen {
+                        None => Evaluator::default(),
+                        Some(pos) => Evaluator::new(*pos),
+                    };
 
-                    for s in moves.iter() {
-                        let whence = match s[..s.ceil_char_boundary(2)].parse() {
-                            Ok(whence) => whence,
+                    for s in moves {
+                        let m: UciMove = match s.parse() {
+                            Ok(m) => m,
                             Err(e) => {
                                 eprintln!("invalid move `{s}`, {e}");
                                 break;
                             }
                         };
 
-                        let moves = self.position.moves().filter(|ms| ms.whence() == whence);
-                        let Some(m) = moves.flatten().find(|m| UciMove(*m) == **s) else {
-                            eprintln!("illegal move `{s}` in position `{}`", self.position);
+                        let moves = self.position.moves().filter(|ms| ms.whence() == m.whence());
+                        let Some(m) = moves.flatten().find(|&n| m == n) else {
+                            eprintln!("illegal move `{m}` in position `{}`", self.position);
                             break;
                         };
 
@@ -202,65 +568,64 @@ impl<I: FusedStream<Item = String> + Unpin, O: Sink<String> + Unpin> Uci<I, O> {
                     }
                 }
 
-                ["eval"] => {
+                UciCommand::Eval => {
                     let pos = &self.position;
                     let turn = self.position.turn();
                     let value = pos.evaluate().perspective(turn);
-                    let info = format!("info value {value:+}");
-                    self.output.send(info).await?;
+                    let material = WdlModel::material(pos);
+                    let cp = WdlModel::default().normalize(value.saturate(), material);
+                    self.report(format!("value {value:+} cp {cp:+}")).await?;
                 }
 
-                ["uci"] => {
-                    let name = "id name Cinder".to_string();
-                    let author = "id author Bruno Dutra".to_string();
+                UciCommand::Stats => {
+                    #[cfg(feature = "stats")]
+                    self.report(format!("stats {}", self.engine.stats()))
+                        .await?;
 
-                    let hash = format!(
-                        "option name Hash type spin default {} min {} max {}",
-                        HashSize::default(),
-                        HashSize::lower(),
-                        HashSize::upper()
-                    );
+                    #[cfg(not(feature = "stats"))]
+                    eprintln!("statistics are only collected with the `stats` feature");
+                }
 
-                    let threads = format!(
-                        "option name Threads type spin default {} min {} max {}",
-                        ThreadCount::default(),
-                        ThreadCount::lower(),
-                        ThreadCount::upper()
-                    );
+                UciCommand::Uci => {
+                    let name = UciResponse::IdName("Cinder".to_string());
+                    let author = UciResponse::IdAuthor("Bruno Dutra".to_string());
 
                     self.output.send(name).await?;
                     self.output.send(author).await?;
-                    self.output.send(hash).await?;
-                    self.output.send(threads).await?;
-                    self.output.send("uciok".to_string()).await?;
+
+                    for (option, _) in Self::options() {
+                        self.output.send(UciResponse::Option(option)).await?;
+                    }
+
+                    #[cfg(feature = "trace")]
+                    for (option, _) in Self::trace_options() {
+                        self.output.send(UciResponse::Option(option)).await?;
+                    }
+
+                    #[cfg(feature = "spsa")]
+                    for (option, _) in Self::params() {
+                        self.output.send(UciResponse::Option(option)).await?;
+                    }
+
+                    self.output.send(UciResponse::UciOk).await?;
                 }
 
-                ["ucinewgame"] => {
-                    self.engine = Engine::with_options(&self.options);
+                UciCommand::UciNewGame => {
+                    self.engine.clear();
                     self.position = Evaluator::default();
                 }
 
-                ["isready"] => self.output.send("readyok".to_string()).await?,
+                UciCommand::IsReady => self.output.send(UciResponse::ReadyOk).await?,
 
-                ["setoption", "name", "hash", "value", hash]
-                | ["setoption", "name", "Hash", "value", hash] => match hash.parse() {
-                    Err(e) => eprintln!("{e}"),
-                    Ok(h) => {
-                        self.options.hash = h;
-                        self.engine = Engine::with_options(&self.options);
-                    }
-                },
-
-                ["setoption", "name", "threads", "value", threads]
-                | ["setoption", "name", "Threads", "value", threads] => match threads.parse() {
-                    Err(e) => eprintln!("{e}"),
-                    Ok(t) => {
-                        self.options.threads = t;
-                        self.engine = Engine::with_options(&self.options);
-                    }
-                },
+                UciCommand::SetOption { name, value } => {
+                    self.set_option(&name, value.as_deref()).await?
+                }
 
-                cmd => eprintln!("ignored unsupported command `{}`", cmd.join(" ")),
+                cmd @ (UciCommand::Debug(_)
+                | UciCommand::Register { .. }
+                | UciCommand::RegisterLater) => {
+                    eprintln!("ignored unsupported command `{cmd}`")
+                }
             }
         }
 
@@ -271,11 +636,12 @@ impl<I: FusedStream<Item = String> + Unpin, O: Sink<String> + Unpin> Uci<I, O> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{chess::Position, search::Depth};
+    use crate::chess::{Move, Outcome, Position};
+    use derive_more::Deref;
     use futures::executor::block_on;
-    use proptest::sample::Selector;
+    use proptest::sample::{select, Selector};
     use std::task::{Context, Poll};
-    use std::{collections::VecDeque, pin::Pin};
+    use std::{collections::VecDeque, convert::Infallible, pin::Pin};
     use test_strategy::proptest;
 
     #[derive(Debug, Default, Clone, Eq, PartialEq)]
@@ -301,7 +667,31 @@ mod tests {
         }
     }
 
-    type MockUci = Uci<StaticStream, Vec<String>>;
+    #[derive(Debug, Default, Clone, Eq, PartialEq, Deref)]
+    struct StaticSink(Vec<String>);
+
+    impl Sink<UciResponse> for StaticSink {
+        type Error = Infallible;
+
+        fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: UciResponse) -> Result<(), Self::Error> {
+            self.0.push(item.to_string());
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    type MockUci = Uci<StaticStream, StaticSink>;
 
     #[proptest]
     fn handles_position_with_startpos(
@@ -354,6 +744,20 @@ mod tests {
         assert!(uci.output.is_empty());
     }
 
+    #[proptest]
+    fn handles_position_with_repeated_moves(
+        #[any(StaticStream::new(["position startpos moves g1f3 g8f6 f3g1 f6g8 g1f3 g8f6 f3g1 f6g8"]))]
+        mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.position.repetitions(), 2);
+        assert_eq!(
+            uci.position.outcome(),
+            Some(Outcome::DrawByThreefoldRepetition)
+        );
+        assert!(uci.output.is_empty());
+    }
+
     #[proptest]
     fn handles_position_with_invalid_move(
         #[strategy("[^[:ascii:]]+")] _s: String,
@@ -364,10 +768,24 @@ mod tests {
         assert!(uci.output.is_empty());
     }
 
+    #[proptest]
+    fn handles_position_with_moves_before_invalid_one(
+        #[any(StaticStream::new(["position startpos moves e2e4 e7e5 invalid g1f3"]))]
+        mut uci: MockUci,
+    ) {
+        let mut pos = Evaluator::default();
+        for m in ["e2e4", "e7e5"] {
+            pos.play(pos.moves().flatten().find(|n| n.to_string() == m).unwrap());
+        }
+
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.position, pos);
+        assert!(uci.output.is_empty());
+    }
+
     #[proptest]
     fn handles_position_with_illegal_move(
-        #[filter(!Position::default().moves().flatten().any(|m| UciMove(m) == *#_m.to_string()))]
-        _m: Move,
+        #[filter(!Position::default().moves().flatten().any(|m| UciMove::from(#_m) == m))] _m: Move,
         #[any(StaticStream::new([format!("position startpos moves {}", #_m)]))] mut uci: MockUci,
     ) {
         assert_eq!(block_on(uci.run()), Ok(()));
@@ -411,6 +829,107 @@ mod tests {
         assert!(uci.output.concat().contains("bestmove"));
     }
 
+    #[proptest]
+    fn handles_go_mate(
+        #[filter(#uci.position.outcome().is_none())]
+        #[any(StaticStream::new([format!("go mate {} nodes {}", #_n, #_nodes)]))]
+        mut uci: MockUci,
+        #[strategy(..3u32)] _n: u32,
+        #[strategy(..1000u64)] _nodes: u64,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert!(uci.output.concat().contains("bestmove"));
+    }
+
+    #[proptest]
+    fn go_mate_reports_solution(
+        #[any(StaticStream::new([
+            "setoption name Hash value 16",
+            "position fen k7/8/8/8/8/8/1R6/2R3K1 w - - 0 1",
+            "go mate 2",
+        ]))]
+        mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+
+        let output = uci.output.concat();
+        assert!(output.contains("score mate 1"));
+        assert!(output.contains("info string solution c1a1"));
+        assert!(output.contains("bestmove c1a1"));
+    }
+
+    #[proptest]
+    fn go_mate_falls_back_to_regular_search_if_there_is_no_mate(
+        #[any(StaticStream::new(["position startpos", "go mate 1"]))] mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+
+        let output = uci.output.concat();
+        assert!(output.contains("info string no mate in 1"));
+        assert!(output.contains("bestmove"));
+    }
+
+    #[proptest]
+    fn go_mate_can_be_stopped(
+        #[any(StaticStream::new(["position startpos", "go mate 5", "stop"]))] mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert!(uci.output.concat().contains("bestmove"));
+    }
+
+    #[proptest]
+    fn go_mate_can_be_interrupted_by_quit(
+        #[any(StaticStream::new(["position startpos", "go mate 5", "quit", "go depth 1"]))]
+        mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert!(!uci.output.concat().contains("bestmove"));
+    }
+
+    #[proptest]
+    fn remaining_limits_exclude_what_was_spent_looking_for_mate(
+        t: Duration,
+        i: Duration,
+        m: Option<u32>,
+        n: u64,
+        spent: Duration,
+        nodes: u64,
+    ) {
+        assert_eq!(
+            MockUci::remaining(&Limits::Time(t), spent, nodes, false),
+            Limits::Time(t.saturating_sub(spent))
+        );
+
+        assert_eq!(
+            MockUci::remaining(&Limits::Clock(t, i, m), spent, nodes, false),
+            Limits::Clock(t.saturating_sub(spent), i, m)
+        );
+
+        assert_eq!(
+            MockUci::remaining(&Limits::Nodes(n), spent, nodes, false),
+            Limits::Nodes(n.saturating_sub(nodes))
+        );
+    }
+
+    #[proptest]
+    fn remaining_limits_are_shallow_if_mate_solver_was_stopped(
+        d: Depth,
+        n: u64,
+        t: Duration,
+        spent: Duration,
+        nodes: u64,
+    ) {
+        for l in [
+            Limits::None,
+            Limits::Depth(d),
+            Limits::Nodes(n),
+            Limits::Time(t),
+        ] {
+            let limits = MockUci::remaining(&l, spent, nodes, true);
+            assert_eq!(limits, Limits::Depth(Depth::new(1)));
+        }
+    }
+
     #[proptest]
     fn handles_go_time(
         #[filter(#uci.position.outcome().is_none())]
@@ -444,6 +963,14 @@ mod tests {
         assert!(uci.output.concat().contains("bestmove"));
     }
 
+    #[proptest]
+    fn go_can_be_interrupted_by_quit(
+        #[any(StaticStream::new(["go", "quit", "go depth 1"]))] mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert!(uci.output.concat().matches("bestmove").count() <= 1);
+    }
+
     #[proptest]
     fn handles_stop(#[any(StaticStream::new(["stop"]))] mut uci: MockUci) {
         assert_eq!(block_on(uci.run()), Ok(()));
@@ -464,15 +991,29 @@ mod tests {
             Color::Black => -pos.evaluate(),
         };
 
-        let value = format!("value {:+}", value);
+        let material = WdlModel::material(&pos);
+        let cp = WdlModel::default().normalize(value.saturate(), material);
+        let value = format!("value {value:+} cp {cp:+}");
         assert_eq!(block_on(uci.run()), Ok(()));
         assert!(uci.output.concat().ends_with(&value));
     }
 
+    #[cfg(feature = "stats")]
+    #[proptest]
+    fn handles_stats(#[any(StaticStream::new(["stats"]))] mut uci: MockUci) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        let stats = format!("info string stats {}", uci.engine.stats());
+        assert!(uci.output.concat().ends_with(&stats));
+    }
+
     #[proptest]
     fn handles_uci(#[any(StaticStream::new(["uci"]))] mut uci: MockUci) {
         assert_eq!(block_on(uci.run()), Ok(()));
         assert!(uci.output.concat().ends_with("uciok"));
+
+        for (option, _) in MockUci::options() {
+            assert!(uci.output.contains(&option.to_string()));
+        }
     }
 
     #[proptest]
@@ -499,14 +1040,14 @@ mod tests {
     }
 
     #[proptest]
-    fn ignores_invalid_hash_size(
+    fn reports_invalid_hash_size(
         #[any(StaticStream::new([format!("setoption name Hash value {}", #_s)]))] mut uci: MockUci,
         #[filter(#_s.trim().parse::<HashSize>().is_err())] _s: String,
     ) {
         let o = uci.options.clone();
         assert_eq!(block_on(uci.run()), Ok(()));
         assert_eq!(uci.options, o);
-        assert!(uci.output.is_empty());
+        assert!(uci.output.concat().starts_with("info string"));
     }
 
     #[proptest]
@@ -521,7 +1062,7 @@ mod tests {
     }
 
     #[proptest]
-    fn ignores_invalid_thread_count(
+    fn reports_invalid_thread_count(
         #[any(StaticStream::new([format!("setoption name Threads value {}", #_s)]))]
         mut uci: MockUci,
         #[filter(#_s.trim().parse::<ThreadCount>().is_err())] _s: String,
@@ -529,9 +1070,190 @@ mod tests {
         let o = uci.options.clone();
         assert_eq!(block_on(uci.run()), Ok(()));
         assert_eq!(uci.options, o);
+        assert!(uci.output.concat().starts_with("info string"));
+    }
+
+    #[proptest]
+    fn handles_option_move_overhead(
+        #[any(StaticStream::new([format!("setoption name Move Overhead value {}", #ms)]))]
+        mut uci: MockUci,
+        #[strategy(..=5000u64)] ms: u64,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options.overhead, Duration::from_millis(ms));
+        assert!(uci.output.is_empty());
+    }
+
+    #[proptest]
+    fn handles_option_deterministic(
+        #[any(StaticStream::new([format!("setoption name Deterministic value {}", #b)]))]
+        mut uci: MockUci,
+        b: bool,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options.deterministic, b);
+        assert!(uci.output.is_empty());
+    }
+
+    #[proptest]
+    fn handles_option_show_wdl(
+        #[any(StaticStream::new([format!("setoption name UCI_ShowWDL value {}", #b)]))]
+        mut uci: MockUci,
+        b: bool,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.show_wdl, b);
+        assert!(uci.output.is_empty());
+    }
+
+    #[proptest]
+    fn go_reports_wdl_if_enabled(
+        #[any(StaticStream::new([
+            "position startpos".to_string(),
+            format!("setoption name UCI_ShowWDL value {}", #b),
+            "go depth 1".to_string(),
+        ]))]
+        mut uci: MockUci,
+        b: bool,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.output.concat().contains(" wdl "), b);
+    }
+
+    #[proptest]
+    fn handles_option_clear_hash(
+        #[any(StaticStream::new(["setoption name Clear Hash"]))] mut uci: MockUci,
+    ) {
+        let o = uci.options.clone();
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options, o);
+        assert!(uci.output.is_empty());
+    }
+
+    #[proptest]
+    fn handles_option_names_case_insensitively(
+        #[any(StaticStream::new([format!("setoption name {} value {}", #_name, #t)]))]
+        mut uci: MockUci,
+        #[strategy(select(&["threads", "THREADS", "tHrEaDs"][..]))] _name: &'static str,
+        t: ThreadCount,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options.threads, t);
+        assert!(uci.output.is_empty());
+    }
+
+    #[proptest]
+    fn handles_multi_word_option_names(
+        #[any(StaticStream::new([format!("setoption name {}", #_name)]))] mut uci: MockUci,
+        #[strategy(select(&["clear hash", "CLEAR  HASH", "Clear\tHash"][..]))] _name: &'static str,
+    ) {
+        let o = uci.options.clone();
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options, o);
+        assert!(uci.output.is_empty());
+    }
+
+    #[proptest]
+    fn reports_out_of_range_option_values(
+        #[any(StaticStream::new([format!("setoption name Threads value {}", #_n)]))]
+        mut uci: MockUci,
+        #[strategy(ThreadCount::MAX + 1..1 << 32)] _n: usize,
+    ) {
+        let o = uci.options.clone();
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options, o);
+        assert!(uci.output.concat().starts_with("info string"));
+        assert!(uci.output.concat().contains("range"));
+    }
+
+    #[cfg(feature = "spsa")]
+    #[proptest]
+    fn handles_search_parameter_options(
+        #[any(StaticStream::new([format!("setoption name {} value {}", #p.name, #v)]))]
+        mut uci: MockUci,
+        #[strategy(select(Params::LIST))] p: Param,
+        #[strategy(#p.min..=#p.max)] v: i16,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.options.params.get(p.name), Some(v));
+        assert!(uci.output.is_empty());
+    }
+
+    #[cfg(feature = "trace")]
+    #[proptest]
+    fn handles_trace_options(
+        #[any(StaticStream::new([
+            format!("setoption name Trace Plies value {}", #p),
+            format!("setoption name Trace Nodes value {}", #n),
+        ]))]
+        mut uci: MockUci,
+        #[filter(#p >= 0)] p: Ply,
+        #[strategy(..=i64::MAX as u64)] n: u64,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.trace.plies, p);
+        assert_eq!(uci.trace.nodes, n);
         assert!(uci.output.is_empty());
     }
 
+    /// The file traces are recorded into by tests, unique to each test run.
+    #[cfg(feature = "trace")]
+    fn trace_file() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("cinder-uci-{}.trace", std::process::id()))
+    }
+
+    #[cfg(feature = "trace")]
+    #[proptest]
+    fn records_search_tree_into_trace_file(
+        #[any(StaticStream::new([
+            "position startpos".to_string(),
+            format!("setoption name Trace File value {}", trace_file().display()),
+            "go depth 1".to_string(),
+        ]))]
+        mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        let file = std::io::BufReader::new(std::fs::File::open(trace_file())?);
+        let nodes = crate::search::TraceReader::new(file).collect::<Result<Vec<_>, _>>()?;
+        assert!(nodes.iter().any(|n| n.path.is_empty()));
+    }
+
+    #[cfg(feature = "trace")]
+    #[proptest]
+    fn reports_failure_to_create_trace_file(
+        #[any(StaticStream::new([
+            "position startpos".to_string(),
+            format!("setoption name Trace File value {}", std::env::temp_dir().display()),
+            "go depth 1".to_string(),
+        ]))]
+        mut uci: MockUci,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert!(uci.output.concat().contains("failed to create trace file"));
+    }
+
+    #[proptest]
+    fn reports_unknown_options(
+        #[any(StaticStream::new([format!("setoption name {} value 1", #_s)]))] mut uci: MockUci,
+        #[strategy("[a-z]{1,8}")]
+        #[filter(MockUci::options().iter().all(|(o, _)| !o.is(&#_s)) && #_s != "value")]
+        _s: String,
+    ) {
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert!(uci
+            .output
+            .concat()
+            .starts_with("info string unknown option"));
+    }
+
+    #[test]
+    fn can_be_driven_by_typed_commands() {
+        let input = stream::iter([UciCommand::IsReady, UciCommand::Quit, UciCommand::Uci]);
+        let mut uci = Uci::new(input.fuse(), Vec::new());
+        assert_eq!(block_on(uci.run()), Ok(()));
+        assert_eq!(uci.output, [UciResponse::ReadyOk]);
+    }
+
     #[proptest]
     fn ignores_unsupported_messages(
         #[any(StaticStream::new([#_s]))] mut uci: MockUci,
@@ -0,0 +1,459 @@
+use crate::chess::{ParsePositionError, Position};
+use crate::uci::UciMove;
+use derive_more::{Display, Error, From};
+use std::fmt::{self, Formatter};
+use std::{str::FromStr, time::Duration};
+
+#[cfg(test)]
+use proptest::{collection::vec, option::of, prelude::*};
+
+/// The parameters of the `go` command.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct UciGo {
+    /// Restricts the search to these moves.
+    #[cfg_attr(test, strategy(vec(any::<UciMove>(), ..4)))]
+    pub searchmoves: Vec<UciMove>,
+    /// Search in pondering mode.
+    pub ponder: bool,
+    /// White's remaining time.
+    #[cfg_attr(test, strategy(of(super::millis())))]
+    pub wtime: Option<Duration>,
+    /// Black's remaining time.
+    #[cfg_attr(test, strategy(of(super::millis())))]
+    pub btime: Option<Duration>,
+    /// White's increment per move.
+    #[cfg_attr(test, strategy(of(super::millis())))]
+    pub winc: Option<Duration>,
+    /// Black's increment per move.
+    #[cfg_attr(test, strategy(of(super::millis())))]
+    pub binc: Option<Duration>,
+    /// The number of moves until the next time control.
+    pub movestogo: Option<u32>,
+    /// Search this many plies only.
+    pub depth: Option<u32>,
+    /// Search this many nodes only.
+    pub nodes: Option<u64>,
+    /// Search for a mate in this many moves.
+    pub mate: Option<u32>,
+    /// Search for exactly this long.
+    #[cfg_attr(test, strategy(of(super::millis())))]
+    pub movetime: Option<Duration>,
+    /// Search until `stop`.
+    pub infinite: bool,
+}
+
+/// A message sent from the GUI to the engine.
+///
+/// See the [UCI specification] for details.
+///
+/// [UCI specification]: https://www.wbec-ridderkerk.nl/html/UCIProtocol.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub enum UciCommand {
+    /// Tells the engine to switch to UCI mode.
+    Uci,
+
+    /// Switches the debug mode on or off.
+    Debug(bool),
+
+    /// Synchronizes the engine with the GUI.
+    IsReady,
+
+    /// Changes the value of an option.
+    SetOption {
+        #[cfg_attr(test, strategy("[A-Z][a-z]{0,7}( [A-Z][a-z]{0,7}){0,2}"))]
+        name: String,
+        #[cfg_attr(test, strategy(of("[a-z0-9]{1,8}( [a-z0-9]{1,8}){0,2}")))]
+        value: Option<String>,
+    },
+
+    /// Postpones registration.
+    RegisterLater,
+
+    /// Registers the engine.
+    Register {
+        #[cfg_attr(test, strategy(of("[A-Z][a-z]{0,7}( [A-Z][a-z]{0,7}){0,2}")))]
+        name: Option<String>,
+        #[cfg_attr(test, strategy(of("[0-9]{1,8}")))]
+        code: Option<String>,
+    },
+
+    /// Signals the next search will be from a different game.
+    UciNewGame,
+
+    /// Sets up the position, either from the starting position or a FEN string.
+    ///
+    /// The moves are kept as given, so that they can be validated as they are played.
+    Position {
+        fen: Option<Box<Position>>,
+        #[cfg_attr(test, strategy(vec(any::<UciMove>().prop_map(|m| m.to_string()), ..4)))]
+        moves: Vec<String>,
+    },
+
+    /// Starts searching the current position.
+    Go(UciGo),
+
+    /// Stops searching as soon as possible.
+    Stop,
+
+    /// Signals the opponent played the expected move.
+    PonderHit,
+
+    /// Quits the engine as soon as possible.
+    Quit,
+
+    /// Reports the static evaluation of the current position.
+    ///
+    /// This is a non-standard extension.
+    Eval,
+
+    /// Reports the time taken to search the current position.
+    ///
+    /// This is a non-standard extension.
+    Bench(UciGo),
+
+    /// Reports statistics collected during the last search.
+    ///
+    /// This is a non-standard extension.
+    Stats,
+}
+
+impl Display for UciGo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.searchmoves.is_empty() {
+            f.write_str(" searchmoves")?;
+            for m in &self.searchmoves {
+                write!(f, " {m}")?;
+            }
+        }
+
+        if self.ponder {
+            f.write_str(" ponder")?;
+        }
+
+        let times = [
+            ("wtime", self.wtime),
+            ("btime", self.btime),
+            ("winc", self.winc),
+            ("binc", self.binc),
+        ];
+
+        for (key, value) in times {
+            if let Some(t) = value {
+                write!(f, " {key} {}", t.as_millis())?;
+            }
+        }
+
+        if let Some(n) = self.movestogo {
+            write!(f, " movestogo {n}")?;
+        }
+
+        if let Some(d) = self.depth {
+            write!(f, " depth {d}")?;
+        }
+
+        if let Some(n) = self.nodes {
+            write!(f, " nodes {n}")?;
+        }
+
+        if let Some(n) = self.mate {
+            write!(f, " mate {n}")?;
+        }
+
+        if let Some(t) = self.movetime {
+            write!(f, " movetime {}", t.as_millis())?;
+        }
+
+        if self.infinite {
+            f.write_str(" infinite")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for UciCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            UciCommand::Uci => f.write_str("uci"),
+            UciCommand::Debug(true) => f.write_str("debug on"),
+            UciCommand::Debug(false) => f.write_str("debug off"),
+            UciCommand::IsReady => f.write_str("isready"),
+
+            UciCommand::SetOption { name, value } => {
+                write!(f, "setoption name {name}")?;
+                if let Some(v) = value {
+                    write!(f, " value {v}")?;
+                }
+
+                Ok(())
+            }
+
+            UciCommand::RegisterLater => f.write_str("register later"),
+
+            UciCommand::Register { name, code } => {
+                f.write_str("register")?;
+
+                if let Some(name) = name {
+                    write!(f, " name {name}")?;
+                }
+
+                if let Some(code) = code {
+                    write!(f, " code {code}")?;
+                }
+
+                Ok(())
+            }
+
+            UciCommand::UciNewGame => f.write_str("ucinewgame"),
+
+            UciCommand::Position { fen, moves } => {
+                match fen {
+                    None => f.write_str("position startpos")?,
+                    Some(pos) => write!(f, "position fen {pos}")?,
+                }
+
+                if !moves.is_empty() {
+                    f.write_str(" moves")?;
+                    for m in moves {
+                        write!(f, " {m}")?;
+                    }
+                }
+
+                Ok(())
+            }
+
+            UciCommand::Go(go) => write!(f, "go{go}"),
+            UciCommand::Stop => f.write_str("stop"),
+            UciCommand::PonderHit => f.write_str("ponderhit"),
+            UciCommand::Quit => f.write_str("quit"),
+            UciCommand::Eval => f.write_str("eval"),
+            UciCommand::Bench(go) => write!(f, "bench{go}"),
+            UciCommand::Stats => f.write_str("stats"),
+        }
+    }
+}
+
+/// The reason why parsing [`UciCommand`] failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error, From)]
+pub enum ParseUciCommandError {
+    #[display("unsupported command")]
+    Unsupported,
+    #[display("invalid arguments")]
+    InvalidArguments,
+    #[display("invalid position, {_0}")]
+    InvalidPosition(ParsePositionError),
+}
+
+impl FromStr for UciGo {
+    type Err = ParseUciCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ParseUciCommandError::*;
+
+        fn millis(value: Option<&str>) -> Result<Option<Duration>, ParseUciCommandError> {
+            // Some GUIs report negative times once the clock runs out.
+            let ms = value.ok_or(InvalidArguments)?.parse::<i64>();
+            let ms = ms.map_err(|_| InvalidArguments)?.max(0);
+            Ok(Some(Duration::from_millis(ms as u64)))
+        }
+
+        fn number<T: FromStr>(value: Option<&str>) -> Result<Option<T>, ParseUciCommandError> {
+            let n = value.ok_or(InvalidArguments)?.parse();
+            Ok(Some(n.map_err(|_| InvalidArguments)?))
+        }
+
+        let mut go = UciGo::default();
+        let mut tokens = s.split_whitespace().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                "searchmoves" => {
+                    while let Some(m) = tokens.peek().and_then(|t| t.parse().ok()) {
+                        go.searchmoves.push(m);
+                        tokens.next();
+                    }
+                }
+
+                "ponder" => go.ponder = true,
+                "wtime" => go.wtime = millis(tokens.next())?,
+                "btime" => go.btime = millis(tokens.next())?,
+                "winc" => go.winc = millis(tokens.next())?,
+                "binc" => go.binc = millis(tokens.next())?,
+                "movestogo" => go.movestogo = number(tokens.next())?,
+                "depth" => go.depth = number(tokens.next())?,
+                "nodes" => go.nodes = number(tokens.next())?,
+                "mate" => go.mate = number(tokens.next())?,
+                "movetime" => go.movetime = millis(tokens.next())?,
+                "infinite" => go.infinite = true,
+                _ => return Err(InvalidArguments),
+            }
+        }
+
+        Ok(go)
+    }
+}
+
+impl FromStr for UciCommand {
+    type Err = ParseUciCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ParseUciCommandError::*;
+
+        let s = s.trim();
+        let (cmd, args) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let tokens: Vec<_> = args.split_whitespace().collect();
+
+        match (cmd, tokens.as_slice()) {
+            ("uci", []) => Ok(UciCommand::Uci),
+            ("debug", ["on"]) => Ok(UciCommand::Debug(true)),
+            ("debug", ["off"]) => Ok(UciCommand::Debug(false)),
+            ("isready", []) => Ok(UciCommand::IsReady),
+            ("ucinewgame", []) => Ok(UciCommand::UciNewGame),
+            ("stop", []) => Ok(UciCommand::Stop),
+            ("ponderhit", []) => Ok(UciCommand::PonderHit),
+            ("quit", []) => Ok(UciCommand::Quit),
+            ("eval", []) => Ok(UciCommand::Eval),
+            ("go", _) => Ok(UciCommand::Go(args.parse()?)),
+            ("bench", _) => Ok(UciCommand::Bench(args.parse()?)),
+            ("stats", []) => Ok(UciCommand::Stats),
+
+            ("setoption", ["name", args @ ..]) => {
+                let (name, value) = match args.iter().position(|a| a.eq_ignore_ascii_case("value"))
+                {
+                    None => (args, None),
+                    Some(i) => (&args[..i], Some(args[i + 1..].join(" "))),
+                };
+
+                if name.is_empty() {
+                    return Err(InvalidArguments);
+                }
+
+                let name = name.join(" ");
+                let value = value.filter(|v| !v.is_empty());
+                Ok(UciCommand::SetOption { name, value })
+            }
+
+            ("register", ["later"]) => Ok(UciCommand::RegisterLater),
+            ("register", args) => {
+                let (mut name, mut code) = (None, None);
+
+                let mut rest = args;
+                while let [key @ ("name" | "code"), tail @ ..] = rest {
+                    let i = tail.iter().position(|&t| t == "name" || t == "code");
+                    let (value, tail) = tail.split_at(i.unwrap_or(tail.len()));
+                    if value.is_empty() {
+                        return Err(InvalidArguments);
+                    } else if *key == "name" {
+                        name = Some(value.join(" "));
+                    } else {
+                        code = Some(value.join(" "));
+                    }
+
+                    rest = tail;
+                }
+
+                if !rest.is_empty() {
+                    return Err(InvalidArguments);
+                }
+
+                Ok(UciCommand::Register { name, code })
+            }
+
+            ("position", [kind, args @ ..]) => {
+                let (fen, moves) = match args.iter().position(|&t| t == "moves") {
+                    None => (args, &[][..]),
+                    Some(i) => (&args[..i], &args[i + 1..]),
+                };
+
+                let fen = match (*kind, fen) {
+                    ("startpos", []) => None,
+                    ("fen", fen @ [_, ..]) => Some(Box::new(fen.join(" ").parse()?)),
+                    _ => return Err(InvalidArguments),
+                };
+
+                let moves = moves.iter().map(|m| m.to_string()).collect();
+                Ok(UciCommand::Position { fen, moves })
+            }
+
+            _ => Err(Unsupported),
+        }
+    }
+}
+
+impl TryFrom<String> for UciCommand {
+    type Error = ParseUciCommandError;
+
+    #[inline(always)]
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn parsing_printed_command_is_an_identity(c: UciCommand) {
+        let s = c.to_string();
+        assert_eq!(s.parse::<UciCommand>().map(|c| c.to_string()), Ok(s));
+    }
+
+    #[proptest]
+    fn parsing_command_ignores_surrounding_whitespace(c: UciCommand) {
+        let s = c.to_string();
+        assert_eq!(format!(" {s}\t").parse::<UciCommand>(), s.parse());
+    }
+
+    #[proptest]
+    fn parsing_command_fails_for_unsupported_command(
+        #[filter(!#s.trim().starts_with(|c: char| c.is_ascii_alphabetic()))] s: String,
+    ) {
+        assert_eq!(
+            s.parse::<UciCommand>(),
+            Err(ParseUciCommandError::Unsupported)
+        );
+    }
+
+    #[proptest]
+    fn parsing_go_accepts_negative_times(#[strategy(..0i64)] t: i64) {
+        let go = UciGo {
+            wtime: Some(Duration::ZERO),
+            ..UciGo::default()
+        };
+
+        assert_eq!(format!("go wtime {t}").parse(), Ok(UciCommand::Go(go)));
+    }
+
+    #[proptest]
+    fn parsing_go_fails_for_invalid_numbers(
+        #[filter(#s.parse::<u32>().is_err())]
+        #[strategy("[^[:space:]]*")]
+        s: String,
+    ) {
+        let e = ParseUciCommandError::InvalidArguments;
+        assert_eq!(format!("go depth {s}").parse::<UciCommand>(), Err(e));
+    }
+
+    #[proptest]
+    fn parsing_position_keeps_invalid_moves(#[strategy(vec("\\S+", ..4))] moves: Vec<String>) {
+        let cmd = format!("position startpos moves {}", moves.join(" "));
+        assert_eq!(cmd.parse(), Ok(UciCommand::Position { fen: None, moves }));
+    }
+
+    #[proptest]
+    fn parsing_set_option_accepts_case_insensitive_value_keyword(
+        #[strategy("[A-Z][a-z]{0,7}")] name: String,
+        #[strategy("[0-9]{1,8}")] value: String,
+    ) {
+        let cmd = UciCommand::SetOption {
+            name: name.clone(),
+            value: Some(value.clone()),
+        };
+
+        let s = format!("setoption name {name} VALUE {value}");
+        assert_eq!(s.parse(), Ok(cmd));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::chess::{Move, ParseRoleError, ParseSquareError, Role, Square};
+use derive_more::{Display, Error, From};
+use std::fmt::{self, Formatter};
+use std::str::FromStr;
+
+/// A chess move in [long algebraic notation].
+///
+/// Unlike [`Move`], it doesn't depend on the position it applies to.
+///
+/// [long algebraic notation]: https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct UciMove {
+    whence: Square,
+    whither: Square,
+    #[cfg_attr(test, filter(!matches!(#promotion, Some(Role::Pawn | Role::King))))]
+    promotion: Option<Role>,
+}
+
+impl UciMove {
+    /// Constructs a move.
+    #[inline(always)]
+    pub fn new(whence: Square, whither: Square, promotion: Option<Role>) -> Self {
+        UciMove {
+            whence,
+            whither,
+            promotion,
+        }
+    }
+
+    /// The source [`Square`].
+    #[inline(always)]
+    pub fn whence(&self) -> Square {
+        self.whence
+    }
+
+    /// The destination [`Square`].
+    #[inline(always)]
+    pub fn whither(&self) -> Square {
+        self.whither
+    }
+
+    /// The promotion specifier.
+    #[inline(always)]
+    pub fn promotion(&self) -> Option<Role> {
+        self.promotion
+    }
+}
+
+impl From<Move> for UciMove {
+    #[inline(always)]
+    fn from(m: Move) -> Self {
+        UciMove::new(m.whence(), m.whither(), m.promotion())
+    }
+}
+
+impl PartialEq<Move> for UciMove {
+    #[inline(always)]
+    fn eq(&self, other: &Move) -> bool {
+        *self == UciMove::from(*other)
+    }
+}
+
+impl Display for UciMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.whence, f)?;
+        Display::fmt(&self.whither, f)?;
+
+        if let Some(r) = self.promotion {
+            Display::fmt(&r, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The reason why parsing [`UciMove`] failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error, From)]
+pub enum ParseUciMoveError {
+    #[display("failed to parse move")]
+    InvalidSquare(ParseSquareError),
+    #[display("failed to parse move")]
+    InvalidPromotion(ParseRoleError),
+}
+
+impl FromStr for UciMove {
+    type Err = ParseUciMoveError;
+
+    #[inline(always)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let i = s.ceil_char_boundary(2);
+        let j = s.ceil_char_boundary(4);
+
+        let promotion = match &s[j..] {
+            "" => None,
+            r => match r.parse()? {
+                Role::Pawn | Role::King => return Err(ParseRoleError.into()),
+                r => Some(r),
+            },
+        };
+
+        Ok(UciMove::new(s[..i].parse()?, s[i..j].parse()?, promotion))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn parsing_printed_move_is_an_identity(m: UciMove) {
+        assert_eq!(m.to_string().parse(), Ok(m));
+    }
+
+    #[proptest]
+    fn parsing_move_fails_for_invalid_promotion(
+        m: UciMove,
+        #[filter(!matches!(#r, Role::Knight | Role::Bishop | Role::Rook | Role::Queen))] r: Role,
+    ) {
+        let s = format!("{}{}{r}", m.whence(), m.whither());
+        assert!(s.parse::<UciMove>().is_err());
+    }
+
+    #[proptest]
+    fn parsing_move_fails_for_invalid_string(
+        #[filter(#s.parse::<Square>().is_err())] s: String,
+        w: Square,
+    ) {
+        assert!(format!("{s}{w}").parse::<UciMove>().is_err());
+    }
+
+    #[proptest]
+    fn uci_move_compares_equal_to_chess_move(m: Move) {
+        assert_eq!(UciMove::from(m), m);
+        assert_eq!(UciMove::from(m).to_string(), m.to_string());
+    }
+}
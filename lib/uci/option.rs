@@ -0,0 +1,455 @@
+use derive_more::{Display, Error};
+use std::fmt::{self, Formatter};
+use std::str::FromStr;
+
+#[cfg(test)]
+use proptest::{prelude::*, sample::select};
+
+/// The type of a UCI option along with its default value and constraints.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum UciOptionKind {
+    /// An integer in the range `min..=max`.
+    Spin { default: i64, min: i64, max: i64 },
+
+    /// Either `true` or `false`.
+    Check { default: bool },
+
+    /// One of a predefined set of strings.
+    Combo { default: String, vars: Vec<String> },
+
+    /// An arbitrary string.
+    String { default: String },
+
+    /// A command without a value.
+    Button,
+}
+
+/// A value assigned to a UCI option.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum UciOptionValue {
+    Spin(i64),
+    Check(bool),
+    Combo(String),
+    String(String),
+    Button,
+}
+
+/// The reason why parsing the value of a UCI option failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error)]
+pub enum ParseUciOptionValueError {
+    #[display("expected an integer")]
+    InvalidSpin,
+    #[display("expected an integer in the range `({_0}..={_1})`")]
+    OutOfRange(i64, i64),
+    #[display("expected either `true` or `false`")]
+    InvalidCheck,
+    #[display("expected one of `{}`", _0.join("`, `"))]
+    InvalidCombo(#[error(not(source))] Vec<String>),
+    #[display("expected a value")]
+    MissingValue,
+    #[display("expected no value")]
+    UnexpectedValue,
+}
+
+/// The declaration of a UCI option.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct UciOption {
+    #[cfg_attr(test, strategy(select(&["Foo", "Bar Baz", "qux"][..]).prop_map(String::from)))]
+    name: String,
+    kind: UciOptionKind,
+}
+
+#[cfg(test)]
+impl Arbitrary for UciOptionKind {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        const VARS: &[&str] = &["Foo", "Bar Baz", "qux"];
+
+        prop_oneof![
+            (any::<i64>(), any::<i64>())
+                .prop_map(|(a, b)| (a.min(b), a.max(b)))
+                .prop_flat_map(|(min, max)| (min..=max, Just(min), Just(max)))
+                .prop_map(|(default, min, max)| UciOptionKind::Spin { default, min, max }),
+            any::<bool>().prop_map(|default| UciOptionKind::Check { default }),
+            select(VARS).prop_map(|default| UciOptionKind::Combo {
+                default: default.to_string(),
+                vars: VARS.iter().map(|v| v.to_string()).collect(),
+            }),
+            select(&["", "Foo", "Bar Baz", "qux"][..]).prop_map(|default| {
+                UciOptionKind::String {
+                    default: default.to_string(),
+                }
+            }),
+            Just(UciOptionKind::Button),
+        ]
+        .boxed()
+    }
+}
+
+impl UciOption {
+    /// Declares an option.
+    pub fn new(name: impl Into<String>, kind: UciOptionKind) -> Self {
+        let name = name.into();
+        UciOption { name, kind }
+    }
+
+    /// Declares an integer option.
+    pub fn spin(name: impl Into<String>, default: i64, min: i64, max: i64) -> Self {
+        Self::new(name, UciOptionKind::Spin { default, min, max })
+    }
+
+    /// Declares a boolean option.
+    pub fn check(name: impl Into<String>, default: bool) -> Self {
+        Self::new(name, UciOptionKind::Check { default })
+    }
+
+    /// Declares an option that takes one of a predefined set of strings.
+    pub fn combo<V: Into<String>>(
+        name: impl Into<String>,
+        default: impl Into<String>,
+        vars: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let default = default.into();
+        let vars = vars.into_iter().map(Into::into).collect();
+        Self::new(name, UciOptionKind::Combo { default, vars })
+    }
+
+    /// Declares an option that takes an arbitrary string.
+    pub fn string(name: impl Into<String>, default: impl Into<String>) -> Self {
+        let default = default.into();
+        Self::new(name, UciOptionKind::String { default })
+    }
+
+    /// Declares a button.
+    pub fn button(name: impl Into<String>) -> Self {
+        Self::new(name, UciOptionKind::Button)
+    }
+
+    /// The option's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The option's type.
+    pub fn kind(&self) -> &UciOptionKind {
+        &self.kind
+    }
+
+    /// Whether `name` refers to this option.
+    ///
+    /// Option names are case-insensitive and whitespace between words is not significant.
+    pub fn is(&self, name: &str) -> bool {
+        let mut lhs = self.name.split_whitespace();
+        let mut rhs = name.split_whitespace();
+
+        loop {
+            match (lhs.next(), rhs.next()) {
+                (None, None) => return true,
+                (Some(l), Some(r)) if l.eq_ignore_ascii_case(r) => continue,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Parses a value for this option.
+    pub fn parse(&self, value: Option<&str>) -> Result<UciOptionValue, ParseUciOptionValueError> {
+        use {ParseUciOptionValueError::*, UciOptionKind as Kind};
+
+        let value = value.map(str::trim);
+        match (&self.kind, value) {
+            (Kind::Button, None) => Ok(UciOptionValue::Button),
+            (Kind::Button, Some(_)) => Err(UnexpectedValue),
+            (_, None) => Err(MissingValue),
+
+            (&Kind::Spin { min, max, .. }, Some(v)) => match v.parse::<i64>() {
+                Err(_) => Err(InvalidSpin),
+                Ok(i) if (min..=max).contains(&i) => Ok(UciOptionValue::Spin(i)),
+                Ok(_) => Err(OutOfRange(min, max)),
+            },
+
+            (Kind::Check { .. }, Some(v)) if v.eq_ignore_ascii_case("true") => {
+                Ok(UciOptionValue::Check(true))
+            }
+
+            (Kind::Check { .. }, Some(v)) if v.eq_ignore_ascii_case("false") => {
+                Ok(UciOptionValue::Check(false))
+            }
+
+            (Kind::Check { .. }, Some(_)) => Err(InvalidCheck),
+
+            (Kind::Combo { vars, .. }, Some(v)) => {
+                match vars.iter().find(|o| o.eq_ignore_ascii_case(v)) {
+                    Some(o) => Ok(UciOptionValue::Combo(o.clone())),
+                    None => Err(InvalidCombo(vars.clone())),
+                }
+            }
+
+            (Kind::String { .. }, Some("<empty>")) => Ok(UciOptionValue::String(String::new())),
+            (Kind::String { .. }, Some(v)) => Ok(UciOptionValue::String(v.to_string())),
+        }
+    }
+}
+
+impl Display for UciOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+
+        match &self.kind {
+            UciOptionKind::Spin { default, min, max } => {
+                write!(f, "spin default {default} min {min} max {max}")
+            }
+
+            UciOptionKind::Check { default } => write!(f, "check default {default}"),
+
+            UciOptionKind::Combo { default, vars } => {
+                write!(f, "combo default {default}")?;
+                vars.iter().try_for_each(|v| write!(f, " var {v}"))
+            }
+
+            UciOptionKind::String { default } if default.is_empty() => {
+                write!(f, "string default <empty>")
+            }
+
+            UciOptionKind::String { default } => write!(f, "string default {default}"),
+            UciOptionKind::Button => write!(f, "button"),
+        }
+    }
+}
+
+/// The reason why parsing [`UciOption`] failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error)]
+#[display("failed to parse option")]
+pub struct ParseUciOptionError;
+
+impl FromStr for UciOption {
+    type Err = ParseUciOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<_> = s.split_whitespace().collect();
+
+        let ["option", "name", tail @ ..] = tokens.as_slice() else {
+            return Err(ParseUciOptionError);
+        };
+
+        let i = tail.iter().position(|&t| t == "type");
+        let (name, [_, kind, args @ ..]) = tail.split_at(i.ok_or(ParseUciOptionError)?) else {
+            return Err(ParseUciOptionError);
+        };
+
+        if name.is_empty() {
+            return Err(ParseUciOptionError);
+        }
+
+        let name = name.join(" ");
+        let kind = match (*kind, args) {
+            ("button", []) => UciOptionKind::Button,
+
+            ("check", ["default", default]) => UciOptionKind::Check {
+                default: default.parse().map_err(|_| ParseUciOptionError)?,
+            },
+
+            ("string", ["default", default @ ..]) => match default.join(" ").as_str() {
+                "<empty>" => UciOptionKind::String {
+                    default: String::new(),
+                },
+
+                default => UciOptionKind::String {
+                    default: default.to_string(),
+                },
+            },
+
+            ("spin", args) => {
+                let (mut default, mut min, mut max) = (None, None, None);
+                for pair in args.chunks(2) {
+                    let [key, value] = pair else {
+                        return Err(ParseUciOptionError);
+                    };
+
+                    let value = value.parse().map_err(|_| ParseUciOptionError)?;
+                    match *key {
+                        "default" => default = Some(value),
+                        "min" => min = Some(value),
+                        "max" => max = Some(value),
+                        _ => return Err(ParseUciOptionError),
+                    }
+                }
+
+                UciOptionKind::Spin {
+                    default: default.ok_or(ParseUciOptionError)?,
+                    min: min.ok_or(ParseUciOptionError)?,
+                    max: max.ok_or(ParseUciOptionError)?,
+                }
+            }
+
+            ("combo", ["default", args @ ..]) => {
+                let mut groups = args.split(|&t| t == "var").map(|g| g.join(" "));
+                let default = groups.next().ok_or(ParseUciOptionError)?;
+                let vars: Vec<_> = groups.collect();
+                if default.is_empty() || vars.iter().any(String::is_empty) {
+                    return Err(ParseUciOptionError);
+                }
+
+                UciOptionKind::Combo { default, vars }
+            }
+
+            _ => return Err(ParseUciOptionError),
+        };
+
+        Ok(UciOption { name, kind })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn option_is_referred_to_by_name_case_insensitively(o: UciOption) {
+        assert!(o.is(o.name()));
+        assert!(o.is(&o.name().to_lowercase()));
+        assert!(o.is(&o.name().to_uppercase()));
+        assert!(o.is(&format!(" {} ", o.name().replace(' ', "  "))));
+    }
+
+    #[proptest]
+    fn option_is_not_referred_to_by_other_names(
+        o: UciOption,
+        #[filter(!#s.to_lowercase().split_whitespace().eq(#o.name().to_lowercase().split_whitespace()))]
+        s: String,
+    ) {
+        assert!(!o.is(&s));
+    }
+
+    #[proptest]
+    fn printed_option_starts_with_name(o: UciOption) {
+        assert!(o
+            .to_string()
+            .starts_with(&format!("option name {} type ", o.name())));
+    }
+
+    #[proptest]
+    fn parsing_printed_option_is_an_identity(o: UciOption) {
+        assert_eq!(o.to_string().parse(), Ok(o));
+    }
+
+    #[proptest]
+    fn parsing_option_fails_for_invalid_string(
+        #[filter(!#s.trim_start().starts_with("option name "))] s: String,
+    ) {
+        assert_eq!(s.parse::<UciOption>(), Err(ParseUciOptionError));
+    }
+
+    #[proptest]
+    fn spin_accepts_integers_in_range(
+        #[strategy(..0i64)] min: i64,
+        #[strategy(0i64..)] max: i64,
+        #[strategy(#min..=#max)] v: i64,
+    ) {
+        let o = UciOption::spin("Foo", 0, min, max);
+        assert_eq!(o.parse(Some(&v.to_string())), Ok(UciOptionValue::Spin(v)));
+    }
+
+    #[proptest]
+    fn spin_rejects_integers_out_of_range(
+        #[strategy(..0i64)] min: i64,
+        #[strategy(0i64..)] max: i64,
+        #[filter(!(#min..=#max).contains(&#v))] v: i64,
+    ) {
+        let o = UciOption::spin("Foo", 0, min, max);
+        let e = ParseUciOptionValueError::OutOfRange(min, max);
+        assert_eq!(o.parse(Some(&v.to_string())), Err(e));
+    }
+
+    #[proptest]
+    fn spin_rejects_invalid_integers(#[filter(#s.trim().parse::<i64>().is_err())] s: String) {
+        let o = UciOption::spin("Foo", 0, i64::MIN, i64::MAX);
+        let e = ParseUciOptionValueError::InvalidSpin;
+        assert_eq!(o.parse(Some(&s)), Err(e));
+    }
+
+    #[proptest]
+    fn check_accepts_booleans(b: bool) {
+        let o = UciOption::check("Foo", false);
+        assert_eq!(o.parse(Some(&b.to_string())), Ok(UciOptionValue::Check(b)));
+    }
+
+    #[proptest]
+    fn check_rejects_anything_else(
+        #[filter(!["true", "false"].contains(&#s.trim().to_lowercase().as_str()))] s: String,
+    ) {
+        let o = UciOption::check("Foo", false);
+        let e = ParseUciOptionValueError::InvalidCheck;
+        assert_eq!(o.parse(Some(&s)), Err(e));
+    }
+
+    #[proptest]
+    fn combo_accepts_vars_case_insensitively(
+        #[strategy(select(&["Foo", "Bar Baz", "qux"][..]))] v: &'static str,
+    ) {
+        let o = UciOption::combo("Foo", "Foo", ["Foo", "Bar Baz", "qux"]);
+        let value = UciOptionValue::Combo(v.to_string());
+        assert_eq!(o.parse(Some(v)), Ok(value.clone()));
+        assert_eq!(o.parse(Some(&v.to_uppercase())), Ok(value));
+    }
+
+    #[proptest]
+    fn combo_rejects_unknown_vars(
+        #[filter(!["foo", "bar baz", "qux"].contains(&#s.trim().to_lowercase().as_str()))]
+        s: String,
+    ) {
+        let o = UciOption::combo("Foo", "Foo", ["Foo", "Bar Baz", "qux"]);
+        let vars = vec!["Foo".to_string(), "Bar Baz".to_string(), "qux".to_string()];
+        let e = ParseUciOptionValueError::InvalidCombo(vars);
+        assert_eq!(o.parse(Some(&s)), Err(e));
+    }
+
+    #[proptest]
+    fn string_accepts_anything(#[filter(#s.trim() != "<empty>")] s: String) {
+        let o = UciOption::string("Foo", "");
+        assert_eq!(
+            o.parse(Some(&s)),
+            Ok(UciOptionValue::String(s.trim().to_string()))
+        );
+    }
+
+    #[test]
+    fn string_can_be_empty() {
+        let o = UciOption::string("Foo", "bar");
+        let v = UciOptionValue::String(String::new());
+        assert_eq!(o.parse(Some("<empty>")), Ok(v));
+    }
+
+    #[proptest]
+    fn button_rejects_values(s: String) {
+        let o = UciOption::button("Foo");
+        let e = ParseUciOptionValueError::UnexpectedValue;
+        assert_eq!(o.parse(None), Ok(UciOptionValue::Button));
+        assert_eq!(o.parse(Some(&s)), Err(e));
+    }
+
+    #[proptest]
+    fn options_other_than_buttons_require_a_value(
+        #[filter(#o.kind() != &UciOptionKind::Button)] o: UciOption,
+    ) {
+        let e = ParseUciOptionValueError::MissingValue;
+        assert_eq!(o.parse(None), Err(e));
+    }
+
+    #[proptest]
+    fn default_value_is_accepted(#[filter(#o.kind() != &UciOptionKind::Button)] o: UciOption) {
+        let default = match o.kind() {
+            UciOptionKind::Spin { default, .. } => default.to_string(),
+            UciOptionKind::Check { default } => default.to_string(),
+            UciOptionKind::Combo { default, .. } => default.to_string(),
+            UciOptionKind::String { default } if default.is_empty() => "<empty>".to_string(),
+            UciOptionKind::String { default } => default.to_string(),
+            UciOptionKind::Button => unreachable!(),
+        };
+
+        assert!(o.parse(Some(&default)).is_ok());
+    }
+}
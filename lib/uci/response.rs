@@ -0,0 +1,391 @@
+use crate::uci::{UciMove, UciOption};
+use derive_more::{Display, Error};
+use std::fmt::{self, Formatter};
+use std::{iter::Peekable, str::FromStr, time::Duration};
+
+#[cfg(test)]
+use proptest::{collection::vec, option::of, prelude::*};
+
+/// The status of copy protection or registration checks.
+#[derive(Debug, Display, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub enum UciStatus {
+    #[display("checking")]
+    Checking,
+    #[display("ok")]
+    Ok,
+    #[display("error")]
+    Error,
+}
+
+/// The score reported in [`UciInfo`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub enum UciScore {
+    /// The score in centipawns from the engine's point of view.
+    Cp(i32),
+    /// Mate in this many moves, negative if the engine is getting mated.
+    Mate(i32),
+}
+
+/// Whether the [`UciScore`] is only a bound.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub enum UciBound {
+    Lower,
+    Upper,
+}
+
+/// The search information sent by the engine.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub struct UciInfo {
+    /// The search depth in plies.
+    pub depth: Option<u32>,
+    /// The selective search depth in plies.
+    pub seldepth: Option<u32>,
+    /// The time searched.
+    #[cfg_attr(test, strategy(of(super::millis())))]
+    pub time: Option<Duration>,
+    /// The number of nodes searched.
+    pub nodes: Option<u64>,
+    /// The best line found.
+    #[cfg_attr(test, strategy(vec(any::<UciMove>(), ..4)))]
+    pub pv: Vec<UciMove>,
+    /// The index of this line in multi-pv mode.
+    pub multipv: Option<u32>,
+    /// The score of the best line.
+    pub score: Option<UciScore>,
+    /// Whether the score is only a bound.
+    pub bound: Option<UciBound>,
+    /// The expected wins, draws, and losses per mille.
+    pub wdl: Option<(u32, u32, u32)>,
+    /// The move currently being searched.
+    pub currmove: Option<UciMove>,
+    /// The index of the move currently being searched.
+    pub currmovenumber: Option<u32>,
+    /// How full the hash is in permill.
+    pub hashfull: Option<u32>,
+    /// The number of nodes searched per second.
+    pub nps: Option<u64>,
+    /// The number of endgame tablebase hits.
+    pub tbhits: Option<u64>,
+    /// The number of shredder endgame database hits.
+    pub sbhits: Option<u64>,
+    /// The CPU usage in permill.
+    pub cpuload: Option<u32>,
+    /// A move refuted by the line that follows it.
+    #[cfg_attr(test, strategy(vec(any::<UciMove>(), ..4)))]
+    pub refutation: Vec<UciMove>,
+    /// The line currently being searched by some CPU.
+    #[cfg_attr(test, strategy(of((any::<Option<u32>>(), vec(any::<UciMove>(), 1..4)))))]
+    pub currline: Option<(Option<u32>, Vec<UciMove>)>,
+    /// Any string to be displayed by the GUI.
+    #[cfg_attr(test, strategy(of("[a-z0-9]{1,8}( [a-z0-9]{1,8}){0,2}")))]
+    pub string: Option<String>,
+}
+
+/// A message sent from the engine to the GUI.
+///
+/// See the [UCI specification] for details.
+///
+/// [UCI specification]: https://www.wbec-ridderkerk.nl/html/UCIProtocol.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(test, derive(test_strategy::Arbitrary))]
+pub enum UciResponse {
+    /// The name of the engine.
+    IdName(#[cfg_attr(test, strategy("[A-Z][a-z]{0,7}( [A-Z][a-z]{0,7}){0,2}"))] String),
+
+    /// The author of the engine.
+    IdAuthor(#[cfg_attr(test, strategy("[A-Z][a-z]{0,7}( [A-Z][a-z]{0,7}){0,2}"))] String),
+
+    /// Acknowledges the `uci` command.
+    UciOk,
+
+    /// Acknowledges the `isready` command.
+    ReadyOk,
+
+    /// The best move found, optionally followed by the move to ponder on.
+    BestMove(UciMove, Option<UciMove>),
+
+    /// The status of copy protection checks.
+    CopyProtection(UciStatus),
+
+    /// The status of registration checks.
+    Registration(UciStatus),
+
+    /// Information about the search.
+    Info(Box<UciInfo>),
+
+    /// Declares an option supported by the engine.
+    Option(UciOption),
+}
+
+impl Display for UciInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("info")?;
+
+        if let Some(d) = self.depth {
+            write!(f, " depth {d}")?;
+        }
+
+        if let Some(d) = self.seldepth {
+            write!(f, " seldepth {d}")?;
+        }
+
+        if let Some(n) = self.multipv {
+            write!(f, " multipv {n}")?;
+        }
+
+        match self.score {
+            None => {}
+            Some(UciScore::Cp(v)) => write!(f, " score cp {v}")?,
+            Some(UciScore::Mate(n)) => write!(f, " score mate {n}")?,
+        }
+
+        match self.bound {
+            None => {}
+            Some(UciBound::Lower) => f.write_str(" lowerbound")?,
+            Some(UciBound::Upper) => f.write_str(" upperbound")?,
+        }
+
+        if let Some((w, d, l)) = self.wdl {
+            write!(f, " wdl {w} {d} {l}")?;
+        }
+
+        if let Some(n) = self.nodes {
+            write!(f, " nodes {n}")?;
+        }
+
+        if let Some(n) = self.nps {
+            write!(f, " nps {n}")?;
+        }
+
+        if let Some(n) = self.hashfull {
+            write!(f, " hashfull {n}")?;
+        }
+
+        if let Some(n) = self.tbhits {
+            write!(f, " tbhits {n}")?;
+        }
+
+        if let Some(n) = self.sbhits {
+            write!(f, " sbhits {n}")?;
+        }
+
+        if let Some(n) = self.cpuload {
+            write!(f, " cpuload {n}")?;
+        }
+
+        if let Some(t) = self.time {
+            write!(f, " time {}", t.as_millis())?;
+        }
+
+        if let Some(m) = self.currmove {
+            write!(f, " currmove {m}")?;
+        }
+
+        if let Some(n) = self.currmovenumber {
+            write!(f, " currmovenumber {n}")?;
+        }
+
+        if !self.refutation.is_empty() {
+            f.write_str(" refutation")?;
+            self.refutation.iter().try_for_each(|m| write!(f, " {m}"))?;
+        }
+
+        if let Some((cpu, line)) = &self.currline {
+            f.write_str(" currline")?;
+
+            if let Some(n) = cpu {
+                write!(f, " {n}")?;
+            }
+
+            line.iter().try_for_each(|m| write!(f, " {m}"))?;
+        }
+
+        if !self.pv.is_empty() {
+            f.write_str(" pv")?;
+            self.pv.iter().try_for_each(|m| write!(f, " {m}"))?;
+        }
+
+        // The string is always last, since it extends to the end of the line.
+        if let Some(s) = &self.string {
+            write!(f, " string {s}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for UciResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            UciResponse::IdName(name) => write!(f, "id name {name}"),
+            UciResponse::IdAuthor(author) => write!(f, "id author {author}"),
+            UciResponse::UciOk => f.write_str("uciok"),
+            UciResponse::ReadyOk => f.write_str("readyok"),
+            UciResponse::BestMove(m, None) => write!(f, "bestmove {m}"),
+            UciResponse::BestMove(m, Some(p)) => write!(f, "bestmove {m} ponder {p}"),
+            UciResponse::CopyProtection(s) => write!(f, "copyprotection {s}"),
+            UciResponse::Registration(s) => write!(f, "registration {s}"),
+            UciResponse::Info(info) => Display::fmt(info, f),
+            UciResponse::Option(option) => Display::fmt(option, f),
+        }
+    }
+}
+
+/// The reason why parsing [`UciResponse`] failed.
+#[derive(Debug, Display, Clone, Eq, PartialEq, Error)]
+#[display("failed to parse uci response")]
+pub struct ParseUciResponseError;
+
+impl FromStr for UciStatus {
+    type Err = ParseUciResponseError;
+
+    #[inline(always)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "checking" => Ok(UciStatus::Checking),
+            "ok" => Ok(UciStatus::Ok),
+            "error" => Ok(UciStatus::Error),
+            _ => Err(ParseUciResponseError),
+        }
+    }
+}
+
+impl FromStr for UciInfo {
+    type Err = ParseUciResponseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn number<T: FromStr>(value: Option<&str>) -> Result<T, ParseUciResponseError> {
+            value
+                .ok_or(ParseUciResponseError)?
+                .parse()
+                .map_err(|_| ParseUciResponseError)
+        }
+
+        fn moves<'a>(tokens: &mut Peekable<impl Iterator<Item = &'a str>>) -> Vec<UciMove> {
+            let mut moves = Vec::new();
+            while let Some(m) = tokens.next_if(|t| t.parse::<UciMove>().is_ok()) {
+                moves.push(m.parse().unwrap_or_else(|_| unreachable!()));
+            }
+
+            moves
+        }
+
+        let mut tokens = s.split_whitespace().peekable();
+        if tokens.next() != Some("info") {
+            return Err(ParseUciResponseError);
+        }
+
+        let mut info = UciInfo::default();
+        while let Some(token) = tokens.next() {
+            match token {
+                "pv" => info.pv = moves(&mut tokens),
+                "refutation" => info.refutation = moves(&mut tokens),
+                "currline" => {
+                    let cpu = tokens.next_if(|t| t.parse::<u32>().is_ok());
+                    let cpu = cpu.map(|t| t.parse()).transpose();
+                    let cpu = cpu.map_err(|_| ParseUciResponseError)?;
+                    info.currline = Some((cpu, moves(&mut tokens)));
+                }
+
+                "string" => {
+                    info.string = Some(tokens.by_ref().collect::<Vec<_>>().join(" "));
+                }
+
+                "score" => {
+                    info.score = match tokens.next() {
+                        Some("cp") => Some(UciScore::Cp(number(tokens.next())?)),
+                        Some("mate") => Some(UciScore::Mate(number(tokens.next())?)),
+                        _ => return Err(ParseUciResponseError),
+                    }
+                }
+
+                "lowerbound" => info.bound = Some(UciBound::Lower),
+                "upperbound" => info.bound = Some(UciBound::Upper),
+                "wdl" => {
+                    let w = number(tokens.next())?;
+                    let d = number(tokens.next())?;
+                    let l = number(tokens.next())?;
+                    info.wdl = Some((w, d, l));
+                }
+
+                "depth" => info.depth = Some(number(tokens.next())?),
+                "seldepth" => info.seldepth = Some(number(tokens.next())?),
+                "multipv" => info.multipv = Some(number(tokens.next())?),
+                "nodes" => info.nodes = Some(number(tokens.next())?),
+                "nps" => info.nps = Some(number(tokens.next())?),
+                "hashfull" => info.hashfull = Some(number(tokens.next())?),
+                "tbhits" => info.tbhits = Some(number(tokens.next())?),
+                "sbhits" => info.sbhits = Some(number(tokens.next())?),
+                "cpuload" => info.cpuload = Some(number(tokens.next())?),
+                "currmovenumber" => info.currmovenumber = Some(number(tokens.next())?),
+                "time" => info.time = Some(Duration::from_millis(number(tokens.next())?)),
+                "currmove" => info.currmove = Some(number(tokens.next())?),
+                _ => return Err(ParseUciResponseError),
+            }
+        }
+
+        Ok(info)
+    }
+}
+
+impl FromStr for UciResponse {
+    type Err = ParseUciResponseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (cmd, args) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let tokens: Vec<_> = args.split_whitespace().collect();
+
+        let parse = |s: &str| s.parse().map_err(|_| ParseUciResponseError);
+
+        match (cmd, tokens.as_slice()) {
+            ("id", ["name", name @ ..]) => Ok(UciResponse::IdName(name.join(" "))),
+            ("id", ["author", author @ ..]) => Ok(UciResponse::IdAuthor(author.join(" "))),
+            ("uciok", []) => Ok(UciResponse::UciOk),
+            ("readyok", []) => Ok(UciResponse::ReadyOk),
+            ("bestmove", [m]) => Ok(UciResponse::BestMove(parse(m)?, None)),
+            ("bestmove", [m, "ponder", p]) => Ok(UciResponse::BestMove(parse(m)?, Some(parse(p)?))),
+            ("copyprotection", [s]) => Ok(UciResponse::CopyProtection(s.parse()?)),
+            ("registration", [s]) => Ok(UciResponse::Registration(s.parse()?)),
+            ("info", _) => Ok(UciResponse::Info(Box::new(s.parse()?))),
+            ("option", _) => Ok(UciResponse::Option(
+                s.parse().map_err(|_| ParseUciResponseError)?,
+            )),
+            _ => Err(ParseUciResponseError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn parsing_printed_response_is_an_identity(r: UciResponse) {
+        assert_eq!(r.to_string().parse(), Ok(r));
+    }
+
+    #[proptest]
+    fn parsing_response_fails_for_invalid_string(
+        #[filter(!#s.trim().starts_with(|c: char| c.is_ascii_alphabetic()))] s: String,
+    ) {
+        assert_eq!(s.parse::<UciResponse>(), Err(ParseUciResponseError));
+    }
+
+    #[proptest]
+    fn info_string_extends_to_the_end_of_the_line(
+        #[strategy("[a-z0-9]{1,8}( [a-z0-9]{1,8}){0,2}")] s: String,
+    ) {
+        let info = UciInfo {
+            string: Some(format!("depth 3 {s}")),
+            ..UciInfo::default()
+        };
+
+        assert_eq!(format!("info string depth 3 {s}").parse(), Ok(info));
+    }
+}
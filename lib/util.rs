@@ -1,19 +1,23 @@
 mod align;
 mod assume;
+mod baton;
 mod binary;
 mod bits;
 mod counter;
 mod integer;
+mod pool;
 mod saturating;
 mod timer;
 mod trigger;
 
 pub use align::*;
 pub use assume::*;
+pub use baton::*;
 pub use binary::*;
 pub use bits::*;
 pub use counter::*;
 pub use integer::*;
+pub use pool::*;
 pub use saturating::*;
 pub use timer::*;
 pub use trigger::*;
@@ -0,0 +1,118 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+#[derive(Debug)]
+struct State {
+    turn: usize,
+    active: Vec<bool>,
+}
+
+/// Grants exclusive turns to a fixed number of participants in round-robin order.
+///
+/// The first turn belongs to the participant `0`.
+#[derive(Debug)]
+pub struct Baton {
+    state: Mutex<State>,
+    signal: Condvar,
+}
+
+impl Baton {
+    /// Constructs a baton shared by `n` participants.
+    #[inline(always)]
+    pub fn new(n: usize) -> Self {
+        Baton {
+            state: Mutex::new(State {
+                turn: 0,
+                active: vec![true; n],
+            }),
+            signal: Condvar::new(),
+        }
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Hands the turn from `id` to the next active participant, if `id` holds it.
+    fn handoff(&self, state: &mut State, id: usize) {
+        if state.turn == id {
+            let n = state.active.len();
+            let next = (1..=n).map(|i| (id + i) % n).find(|&i| state.active[i]);
+            state.turn = next.unwrap_or(id);
+            self.signal.notify_all();
+        }
+    }
+
+    /// Blocks until it's the turn of the `id`-th participant.
+    #[inline(always)]
+    pub fn wait(&self, id: usize) {
+        let state = self.lock();
+        let cond = |s: &mut State| s.turn != id;
+        drop(self.signal.wait_while(state, cond));
+    }
+
+    /// Hands the turn of the `id`-th participant over to the next one and waits for it to return.
+    #[inline(always)]
+    pub fn pass(&self, id: usize) {
+        self.handoff(&mut self.lock(), id);
+        self.wait(id);
+    }
+
+    /// Withdraws the `id`-th participant, handing its turn over to the next one if it holds it.
+    #[inline(always)]
+    pub fn leave(&self, id: usize) {
+        let mut state = self.lock();
+        state.active[id] = false;
+        self.handoff(&mut state, id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{mpsc::channel, Arc};
+    use std::thread;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn baton_grants_turns_in_round_robin_order(
+        #[strategy(1..8usize)] n: usize,
+        #[strategy(..4usize)] rounds: usize,
+    ) {
+        let baton = Arc::new(Baton::new(n));
+        let (tx, rx) = channel();
+
+        let handles = Vec::from_iter((0..n).map(|id| {
+            let (baton, tx) = (baton.clone(), tx.clone());
+            thread::spawn(move || {
+                baton.wait(id);
+                for _ in 0..rounds {
+                    tx.send(id).unwrap();
+                    baton.pass(id);
+                }
+
+                tx.send(id).unwrap();
+                baton.leave(id);
+            })
+        }));
+
+        drop(tx);
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let expected = (0..=rounds).flat_map(|_| 0..n);
+        assert_eq!(Vec::from_iter(rx), Vec::from_iter(expected));
+    }
+
+    #[proptest]
+    fn baton_skips_participants_that_left(#[strategy(2..8usize)] n: usize) {
+        let baton = Baton::new(n);
+        for id in 1..n {
+            baton.leave(id);
+        }
+
+        baton.pass(0);
+        baton.leave(0);
+    }
+}
@@ -15,6 +15,12 @@ impl Counter {
         }
     }
 
+    /// The number of counts remaining.
+    #[inline(always)]
+    pub fn remaining(&self) -> u64 {
+        self.remaining.load(Ordering::Relaxed)
+    }
+
     /// Increments the counter and returns the number of counts remaining if any.
     #[inline(always)]
     pub fn count(&self) -> Option<u64> {
@@ -33,6 +39,7 @@ mod tests {
     fn counter_keeps_track_of_counts_remaining(#[strategy(1u64..)] c: u64) {
         let counter = Counter::new(c);
         assert_eq!(counter.count(), Some(c - 1));
+        assert_eq!(counter.remaining(), c - 1);
     }
 
     #[test]
@@ -0,0 +1,139 @@
+use crate::util::Assume;
+use std::sync::mpsc::{self, SendError, Sender};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+/// A unit of work executed by a [`Pool`] worker.
+pub type Job<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// A pool of persistent worker threads, each owning some private state.
+///
+/// Workers are spawned on demand and parked while waiting for work,
+/// their state surviving from one [`Job`] to the next.
+#[derive(Debug)]
+pub struct Pool<T> {
+    workers: Mutex<Vec<Sender<Job<T>>>>,
+}
+
+impl<T> Default for Pool<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Pool {
+            workers: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Default + 'static> Pool<T> {
+    fn spawn() -> Sender<Job<T>> {
+        let (tx, rx) = mpsc::channel::<Job<T>>();
+
+        thread::spawn(move || {
+            let mut state = T::default();
+            for job in rx {
+                job(&mut state);
+            }
+        });
+
+        tx
+    }
+
+    /// The number of workers spawned so far.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.workers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no workers have been spawned so far.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hands the `i`-th [`Job`] to the `i`-th worker, spawning workers as needed.
+    ///
+    /// Jobs handed to the same worker run in order.
+    pub fn execute(&self, jobs: impl IntoIterator<Item = Job<T>>) {
+        let mut workers = self.workers.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for (i, job) in jobs.into_iter().enumerate() {
+            if workers.len() <= i {
+                workers.push(Self::spawn());
+            }
+
+            if let Err(SendError(job)) = workers[i].send(job) {
+                // The worker died while executing a previous job.
+                workers[i] = Self::spawn();
+                workers[i].send(job).assume();
+            }
+        }
+    }
+
+    /// Retires all but the first `n` workers once they finish their pending jobs.
+    pub fn truncate(&mut self, n: usize) {
+        let workers = self.workers.get_mut();
+        workers.unwrap_or_else(PoisonError::into_inner).truncate(n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use test_strategy::proptest;
+
+    #[proptest]
+    fn pool_spawns_workers_on_demand(#[strategy(..8usize)] n: usize) {
+        let pool = Pool::<()>::default();
+        pool.execute((0..n).map(|_| Box::new(|_: &mut ()| {}) as Job<()>));
+        assert_eq!(pool.len(), n);
+    }
+
+    #[proptest]
+    fn pool_preserves_worker_state_across_jobs(#[strategy(1..8usize)] n: usize) {
+        let pool = Pool::<usize>::default();
+        let (tx, rx) = channel();
+
+        for _ in 0..n {
+            let tx = tx.clone();
+            pool.execute([Box::new(move |s: &mut usize| {
+                *s += 1;
+                tx.send(*s).assume();
+            }) as Job<usize>]);
+        }
+
+        drop(tx);
+        assert_eq!(Vec::from_iter(rx), Vec::from_iter(1..=n));
+    }
+
+    #[proptest]
+    fn pool_runs_jobs_on_distinct_workers(#[strategy(1..8usize)] n: usize) {
+        let pool = Pool::<Option<usize>>::default();
+        let (tx, rx) = channel();
+
+        for _ in 0..2 {
+            pool.execute((0..n).map(|i| {
+                let tx = tx.clone();
+                Box::new(move |s: &mut Option<usize>| {
+                    tx.send(s.replace(i)).assume();
+                }) as Job<Option<usize>>
+            }));
+        }
+
+        drop(tx);
+        let mut seen = Vec::from_iter(rx.into_iter().flatten());
+        seen.sort();
+        assert_eq!(seen, Vec::from_iter(0..n));
+    }
+
+    #[proptest]
+    fn truncate_retires_workers(#[strategy(..8usize)] n: usize, #[strategy(..=#n)] m: usize) {
+        let mut pool = Pool::<()>::default();
+        pool.execute((0..n).map(|_| Box::new(|_: &mut ()| {}) as Job<()>));
+        pool.truncate(m);
+        assert_eq!(pool.len(), m);
+    }
+}